@@ -0,0 +1,43 @@
+//! Times `update` on a 100k-particle sphere swarm.
+//!
+//! Compare the serial and parallel builds with
+//! `cargo bench --bench parallel` and `cargo bench --bench parallel --features rayon`.
+
+use pso::benchmarks::sphere;
+use pso::{update, BoundMode, Bounds, OptimizationPolicy, ParticleSwarm, UpdatePolicy};
+use rand::SeedableRng;
+use std::time::Instant;
+
+const PARTICLES: usize = 100_000;
+const DIMENSION: usize = 10;
+const ITERATIONS: usize = 20;
+
+fn main() {
+    let mut r = rand::rngs::StdRng::seed_from_u64(0);
+    let bounds = Bounds::uniform(-5.0, 5.0, DIMENSION, BoundMode::Clamp);
+    let opt = OptimizationPolicy::FindMinimum;
+    let consts = UpdatePolicy::new(0.729, 1.49445, 1.49445);
+    let ranges = [(-5.0, 5.0); DIMENSION];
+    let mut swarm: ParticleSwarm =
+        ParticleSwarm::new_random(PARTICLES, DIMENSION, &ranges, (-1.0, 1.0), &sphere, &opt, &mut r);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        update(&mut swarm, &consts, Some(&bounds), &[], &[], &sphere, &opt);
+    }
+    let elapsed = start.elapsed();
+
+    let mode = if cfg!(feature = "rayon") {
+        "parallel"
+    } else {
+        "serial"
+    };
+    println!(
+        "{}: {} iterations of {} particles in {:?} ({:?} per iteration)",
+        mode,
+        ITERATIONS,
+        PARTICLES,
+        elapsed,
+        elapsed / ITERATIONS as u32
+    );
+}
@@ -0,0 +1,166 @@
+//! Text charts of the objective with the final swarm on top, for one- and two-dimensional
+//! problems in a terminal.
+
+use crate::Bounds;
+
+/// Lines of the chart of a one-dimensional objective
+const LINE_ROWS: usize = 16;
+
+/// Shades of the heatmap from the lowest value to the highest
+const SHADES: &[u8] = b" .:-=+*#%@";
+
+/// The objective `f` over the bounds, or over the span of the swarm without bounds, sampled at
+/// `cols` points along each coordinate: a line chart for one coordinate and a heatmap for two,
+/// with `o` at every particle of `position` and `X` at `best`
+pub fn landscape(
+    f: &dyn Fn(&[f64]) -> f64,
+    bounds: Option<&Bounds>,
+    position: &[Vec<f64>],
+    best: &[f64],
+    cols: usize,
+) -> String {
+    let ranges = (0..best.len())
+        .map(|j| match bounds {
+            Some(b) => b.range(j),
+            None => span(position.iter().map(|x| x[j]).chain([best[j]])),
+        })
+        .collect::<Vec<(f64, f64)>>();
+    match ranges[..] {
+        [range] => line(f, range, position, best, cols),
+        [x_range, y_range] => heatmap(f, (x_range, y_range), position, best, cols),
+        _ => panic!("only one or two coordinates can be drawn, got {}", ranges.len()),
+    }
+}
+
+/// Smallest and largest of `values`, widened around a single value
+fn span(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (lo, hi) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+        (lo.min(v), hi.max(v))
+    });
+    if lo < hi {
+        (lo, hi)
+    } else {
+        (lo - 1.0, hi + 1.0)
+    }
+}
+
+/// Index of the cell of `cells` equal parts of `range` that `x` falls into, clamped to the cells
+fn cell(x: f64, (lo, hi): (f64, f64), cells: usize) -> usize {
+    let i = ((x - lo) / (hi - lo) * cells as f64).floor();
+    (i.max(0.0) as usize).min(cells - 1)
+}
+
+/// Center of cell `i` of `cells` equal parts of `range`
+fn center(i: usize, (lo, hi): (f64, f64), cells: usize) -> f64 {
+    lo + (i as f64 + 0.5) * (hi - lo) / cells as f64
+}
+
+fn line(
+    f: &dyn Fn(&[f64]) -> f64,
+    range: (f64, f64),
+    position: &[Vec<f64>],
+    best: &[f64],
+    cols: usize,
+) -> String {
+    let y = (0..cols).map(|c| f(&[center(c, range, cols)])).collect::<Vec<f64>>();
+    let y_range = span(y.iter().copied().filter(|yi| yi.is_finite()));
+    let mut grid = vec![vec![b' '; cols]; LINE_ROWS];
+    // Row 0 is the top, the highest value
+    let row = |yi: f64| LINE_ROWS - 1 - cell(yi, y_range, LINE_ROWS);
+    for (c, &yi) in y.iter().enumerate().filter(|(_, yi)| yi.is_finite()) {
+        grid[row(yi)][c] = b'*';
+    }
+    let marks = position.iter().map(|x| (x[0], b'o')).chain([(best[0], b'X')]);
+    for (x, mark) in marks {
+        let yi = f(&[x]);
+        if yi.is_finite() {
+            grid[row(yi)][cell(x, range, cols)] = mark;
+        }
+    }
+
+    let mut chart = String::new();
+    for (r, cells) in grid.iter().enumerate() {
+        let label = match r {
+            0 => format!("{:.3e}", y_range.1),
+            _ if r == LINE_ROWS - 1 => format!("{:.3e}", y_range.0),
+            _ => String::new(),
+        };
+        chart += &format!("{:>10} |{}\n", label, String::from_utf8_lossy(cells));
+    }
+    chart += &format!("{:>10} +{}\n", "", "-".repeat(cols));
+    let (lo, hi) = (format!("{:.3e}", range.0), format!("{:.3e}", range.1));
+    let gap = (cols + 1).saturating_sub(lo.len() + hi.len()).max(1);
+    chart += &format!("{:>10} {}{}{}", "", lo, " ".repeat(gap), hi);
+    chart
+}
+
+fn heatmap(
+    f: &dyn Fn(&[f64]) -> f64,
+    (x_range, y_range): ((f64, f64), (f64, f64)),
+    position: &[Vec<f64>],
+    best: &[f64],
+    cols: usize,
+) -> String {
+    // Characters are about twice as tall as they are wide
+    let rows = cols.div_ceil(2);
+    let z = (0..rows)
+        .map(|r| {
+            let y = center(rows - 1 - r, y_range, rows);
+            (0..cols).map(|c| f(&[center(c, x_range, cols), y])).collect()
+        })
+        .collect::<Vec<Vec<f64>>>();
+    let z_range = span(z.iter().flatten().copied().filter(|zi| zi.is_finite()));
+    let shade = |zi: f64| {
+        if zi.is_finite() {
+            SHADES[cell(zi, z_range, SHADES.len())]
+        } else {
+            b'?'
+        }
+    };
+    let mut grid = z
+        .iter()
+        .map(|row| row.iter().map(|&zi| shade(zi)).collect())
+        .collect::<Vec<Vec<u8>>>();
+    let marks = position.iter().map(|x| (&x[..], b'o')).chain([(best, b'X')]);
+    for (x, mark) in marks {
+        grid[rows - 1 - cell(x[1], y_range, rows)][cell(x[0], x_range, cols)] = mark;
+    }
+
+    let mut chart = String::new();
+    for (r, cells) in grid.iter().enumerate() {
+        let label = match r {
+            0 => format!("{:.3e}", y_range.1),
+            _ if r == rows - 1 => format!("{:.3e}", y_range.0),
+            _ => String::new(),
+        };
+        chart += &format!("{:>10} |{}|\n", label, String::from_utf8_lossy(cells));
+    }
+    let (lo, hi) = (format!("{:.3e}", x_range.0), format!("{:.3e}", x_range.1));
+    let gap = (cols + 2).saturating_sub(lo.len() + hi.len()).max(1);
+    chart += &format!("{:>10} {}{}{}\n", "", lo, " ".repeat(gap), hi);
+    let (lowest, highest) = (SHADES[0] as char, SHADES[SHADES.len() - 1] as char);
+    chart += &format!(
+        "'{}' {:.3e} to '{}' {:.3e}, o particle, X best",
+        lowest, z_range.0, highest, z_range.1
+    );
+    chart
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoundMode;
+
+    #[test]
+    fn line_chart_marks_the_best_at_the_bottom() {
+        let bounds = Bounds::uniform(-5.0, 5.0, 1, BoundMode::Clamp);
+        let sphere = |x: &[f64]| x[0] * x[0];
+        let chart = landscape(&sphere, Some(&bounds), &[vec![2.0]], &[0.0], 40);
+        let lines = chart.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), LINE_ROWS + 2);
+        assert!(lines[..LINE_ROWS].iter().any(|l| l.contains('o')));
+        let bottom = lines[LINE_ROWS - 1];
+        assert_eq!(bottom.as_bytes()[12 + 20], b'X');
+        assert!(lines.iter().all(|l| !l.is_empty()));
+    }
+}
@@ -0,0 +1,153 @@
+//! Standard benchmark functions for testing optimizers, defined for any dimension.
+
+use std::f64::consts::{E, PI};
+
+/// Global minimizer coordinate of the Schwefel function
+const SCHWEFEL_MINIMIZER: f64 = 420.968_746_227_503_4;
+/// Per-dimension offset that makes the Schwefel minimum zero
+const SCHWEFEL_OFFSET: f64 = 418.982_887_272_433_8;
+
+/// A benchmark function selectable by name
+#[derive(Debug, Clone, Copy)]
+pub enum Objective {
+    Sphere,
+    Quadratic,
+    Rastrigin,
+    Ackley,
+    Rosenbrock,
+    Griewank,
+    Schwefel,
+}
+
+impl Objective {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sphere" => Some(Objective::Sphere),
+            "quadratic" => Some(Objective::Quadratic),
+            "rastrigin" => Some(Objective::Rastrigin),
+            "ackley" => Some(Objective::Ackley),
+            "rosenbrock" => Some(Objective::Rosenbrock),
+            "griewank" => Some(Objective::Griewank),
+            "schwefel" => Some(Objective::Schwefel),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Objective::Sphere => "sphere",
+            Objective::Quadratic => "quadratic",
+            Objective::Rastrigin => "rastrigin",
+            Objective::Ackley => "ackley",
+            Objective::Rosenbrock => "rosenbrock",
+            Objective::Griewank => "griewank",
+            Objective::Schwefel => "schwefel",
+        }
+    }
+
+    pub fn function(&self) -> fn(&[f64]) -> f64 {
+        match self {
+            Objective::Sphere => sphere,
+            Objective::Quadratic => quadratic,
+            Objective::Rastrigin => rastrigin,
+            Objective::Ackley => ackley,
+            Objective::Rosenbrock => rosenbrock,
+            Objective::Griewank => griewank,
+            Objective::Schwefel => schwefel,
+        }
+    }
+
+    pub fn formula(&self) -> &'static str {
+        match self {
+            Objective::Sphere => "y = sum(x_i^2)",
+            Objective::Quadratic => "y = (x - 1)^2",
+            Objective::Rastrigin => "y = 10d + sum(x_i^2 - 10cos(2pi x_i))",
+            Objective::Ackley => {
+                "y = -20exp(-0.2sqrt(mean(x_i^2))) - exp(mean(cos(2pi x_i))) + 20 + e"
+            }
+            Objective::Rosenbrock => "y = sum(100(x_{i+1} - x_i^2)^2 + (1 - x_i)^2)",
+            Objective::Griewank => "y = 1 + sum(x_i^2)/4000 - prod(cos(x_i/sqrt(i)))",
+            Objective::Schwefel => "y = 418.9829d - sum(x_i sin(sqrt(|x_i|)))",
+        }
+    }
+}
+
+/// Minimum 0 at x = (0, ..., 0)
+pub fn sphere(x: &[f64]) -> f64 {
+    x.iter().map(|xi| xi * xi).sum()
+}
+
+/// Minimum 0 at x = (1, ..., 1)
+pub fn quadratic(x: &[f64]) -> f64 {
+    x.iter().map(|xi| (xi - 1.0) * (xi - 1.0)).sum()
+}
+
+/// Minimum 0 at x = (0, ..., 0)
+pub fn rastrigin(x: &[f64]) -> f64 {
+    10.0 * x.len() as f64
+        + x.iter()
+            .map(|xi| xi * xi - 10.0 * (2.0 * PI * xi).cos())
+            .sum::<f64>()
+}
+
+/// Minimum 0 at x = (0, ..., 0)
+pub fn ackley(x: &[f64]) -> f64 {
+    let d = x.len() as f64;
+    let squares = x.iter().map(|xi| xi * xi).sum::<f64>() / d;
+    let cosines = x.iter().map(|xi| (2.0 * PI * xi).cos()).sum::<f64>() / d;
+    -20.0 * (-0.2 * squares.sqrt()).exp() - cosines.exp() + 20.0 + E
+}
+
+/// Minimum 0 at x = (1, ..., 1); constant for a single dimension
+pub fn rosenbrock(x: &[f64]) -> f64 {
+    x.windows(2)
+        .map(|p| 100.0 * (p[1] - p[0] * p[0]).powi(2) + (1.0 - p[0]).powi(2))
+        .sum()
+}
+
+/// Minimum 0 at x = (0, ..., 0)
+pub fn griewank(x: &[f64]) -> f64 {
+    let squares = x.iter().map(|xi| xi * xi).sum::<f64>() / 4000.0;
+    let cosines = x
+        .iter()
+        .enumerate()
+        .map(|(i, xi)| (xi / ((i + 1) as f64).sqrt()).cos())
+        .product::<f64>();
+    1.0 + squares - cosines
+}
+
+/// Minimum 0 at x = (420.9687, ..., 420.9687), usually searched in [-500, 500]
+pub fn schwefel(x: &[f64]) -> f64 {
+    SCHWEFEL_OFFSET * x.len() as f64
+        - x.iter()
+            .map(|xi| xi * xi.abs().sqrt().sin())
+            .sum::<f64>()
+}
+
+/// Returns the global minimizer and minimum value of the named benchmark in `d` dimensions
+pub fn known_optimum(name: &str, d: usize) -> Option<(Vec<f64>, f64)> {
+    let minimizer = match name {
+        "sphere" | "rastrigin" | "ackley" | "griewank" => 0.0,
+        "quadratic" | "rosenbrock" => 1.0,
+        "schwefel" => SCHWEFEL_MINIMIZER,
+        _ => return None,
+    };
+    Some((vec![minimizer; d], 0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_benchmark_reaches_its_known_optimum() {
+        let names = ["sphere", "quadratic", "rastrigin", "ackley", "rosenbrock", "griewank", "schwefel"];
+        for name in names {
+            let f = Objective::from_name(name).unwrap().function();
+            for d in [1, 2, 5] {
+                let (x, y) = known_optimum(name, d).unwrap();
+                assert!((f(&x) - y).abs() < 1e-9, "{} in {} dimensions: {}", name, d, f(&x));
+            }
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Cooperative PSO (CPSO-S_K of van den Bergh and Engelbrecht), which splits the coordinates among
+//! sub-swarms that each search their own slice of them.
+//!
+//! A slice alone is no position, so it is evaluated in the context vector: the best slices of all
+//! the sub-swarms put together, with the slice in question in place of its sub-swarm's. A slice
+//! that improves the context replaces the one there. On separable functions every slice can
+//! improve without waiting for the others, which beats a single swarm by far in high dimensions.
+
+use crate::init::{self, InitMethod};
+use crate::{
+    convert, pin, round_integers, update, Bounds, Event, OptimizationPolicy, ParticleSwarm,
+    PsoConfig, RunError, RunResult, Scalar, Spread, StopReason, TIME_CHECK_INTERVAL,
+};
+use indicatif::{ProgressBar, ProgressStyle};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use tracing::{debug, trace};
+
+/// Splits `d` coordinates into `s` consecutive slices, the first `d % s` one coordinate longer
+pub fn split(d: usize, s: usize) -> Vec<Range<usize>> {
+    let (size, longer) = (d / s, d % s);
+    let mut start = 0;
+    (0..s)
+        .map(|j| {
+            let end = start + size + (j < longer) as usize;
+            let slice = start..end;
+            start = end;
+            slice
+        })
+        .collect()
+}
+
+/// `f` at `context` with its coordinates in `slice` replaced by `x`
+fn in_context(
+    f: &(dyn Fn(&[f64]) -> f64 + Sync),
+    context: &[f64],
+    slice: &Range<usize>,
+    x: &[f64],
+) -> f64 {
+    let mut full = context.to_vec();
+    full[slice.clone()].copy_from_slice(x);
+    f(&full)
+}
+
+/// The global best of a sub-swarm, its candidate for the context
+fn global_slice<F: Scalar>(swarm: &ParticleSwarm<F>) -> Vec<f64> {
+    convert(&swarm.local_optimum[swarm.best_index])
+}
+
+/// Mean length of the velocities of the whole particles, put together from all sub-swarms
+fn velocity_norm<F: Scalar>(swarms: &[ParticleSwarm<F>]) -> f64 {
+    let n = swarms[0].velocity.len();
+    let squared = |i: usize| {
+        let v = swarms.iter().flat_map(|swarm| &swarm.velocity[i]);
+        v.map(|&vi| (vi * vi).widen()).sum::<f64>()
+    };
+    (0..n).map(|i| squared(i).sqrt()).sum::<f64>() / n as f64
+}
+
+/// Runs `s` sub-swarms of `config.n` particles each from `r`, with `f` the penalized objective
+/// counting its calls in `evaluations`
+pub(crate) fn run<F: Scalar>(
+    config: &PsoConfig,
+    s: usize,
+    f: &(dyn Fn(&[f64]) -> f64 + Sync),
+    evaluations: &AtomicUsize,
+    r: &mut StdRng,
+    seed: u64,
+) -> Result<RunResult, RunError> {
+    let (n, d, opt) = (config.n, config.d, &config.opt);
+    let slices = split(d, s);
+    let x_ranges = match (config.init_range, config.bounds.as_ref()) {
+        (Some(range), _) => vec![range; d],
+        (None, Some(b)) => (0..d).map(|j| b.range(j)).collect(),
+        (None, None) => vec![(-1.0, 1.0); d],
+    };
+    let (lower, upper) = config.vinit_range.unwrap_or((-1.0, 1.0));
+    let (lower, upper) = (F::of(lower), F::of(upper));
+    let bounds = slices
+        .iter()
+        .map(|slice| {
+            config.bounds.as_ref().map(|b| Bounds {
+                lower: b.lower[slice.clone()].to_vec(),
+                upper: b.upper[slice.clone()].to_vec(),
+                modes: b.modes[slice.clone()].to_vec(),
+            })
+        })
+        .collect::<Vec<Option<Bounds>>>();
+    let integer = slices
+        .iter()
+        .map(|slice| {
+            let dims = config.integer_dims.iter().filter(|j| slice.contains(j));
+            dims.map(|j| j - slice.start).collect()
+        })
+        .collect::<Vec<Vec<usize>>>();
+    let fixed = slices
+        .iter()
+        .map(|slice| {
+            let dims = config.fixed.iter().filter(|(j, _)| slice.contains(j));
+            dims.map(|&(j, value)| (j - slice.start, value)).collect()
+        })
+        .collect::<Vec<Vec<(usize, f64)>>>();
+    let particles = slices
+        .iter()
+        .zip(integer.iter().zip(&fixed))
+        .map(|(slice, (integer, fixed))| {
+            let ranges = &x_ranges[slice.clone()];
+            let mut x: Vec<Vec<F>> = match config.init_method {
+                InitMethod::Uniform => {
+                    let mut draw = |&(lo, hi): &(f64, f64)| r.gen_range(F::of(lo)..=F::of(hi));
+                    (0..n).map(|_| ranges.iter().map(&mut draw).collect()).collect()
+                }
+                InitMethod::Halton => {
+                    let x = init::halton(n, ranges, r);
+                    x.iter().map(|xi| convert(xi)).collect()
+                }
+                InitMethod::LatinHypercube => {
+                    let x = init::latin_hypercube(n, ranges, r);
+                    x.iter().map(|xi| convert(xi)).collect()
+                }
+            };
+            let mut v: Vec<Vec<F>> = (0..n)
+                .map(|_| (0..slice.len()).map(|_| r.gen_range(lower..=upper)).collect())
+                .collect();
+            for (xi, vi) in x.iter_mut().zip(v.iter_mut()) {
+                round_integers(xi, integer);
+                pin(xi, vi, fixed);
+            }
+            (x, v)
+        })
+        .collect::<Vec<(Vec<Vec<F>>, Vec<Vec<F>>)>>();
+
+    // The first particles make up the first context, which every new sub-swarm then improves on
+    let mut context = particles.iter().flat_map(|(x, _)| convert(&x[0])).collect::<Vec<f64>>();
+    let mut value = f64::NAN;
+    let mut swarms = Vec::with_capacity(s);
+    for (slice, (x, v)) in slices.iter().zip(particles) {
+        let g = |x: &[f64]| in_context(f, &context, slice, x);
+        let mut swarm = ParticleSwarm::new(n, slice.len(), x, v, &g, opt);
+        swarm.seed_particles(r.gen());
+        if let Some(weights) = &config.weights {
+            swarm.set_weights(weights.clone(), opt);
+        }
+        value = swarm.global_value();
+        context[slice.clone()].copy_from_slice(&global_slice(&swarm));
+        swarms.push(swarm);
+    }
+    let failed = || config.program.as_ref().and_then(|p| p.take_error());
+    if let Some(reason) = failed() {
+        return Err(RunError::ExternalObjective(reason));
+    }
+    if value.is_nan() {
+        return Err(RunError::AllNaN);
+    }
+    debug!(best_value = value, ?context, "sub-swarms initialized");
+    if config.events {
+        Event::Init {
+            n,
+            d,
+            seed,
+            i: 0,
+            best: value,
+        }
+        .emit();
+    }
+
+    let start = Instant::now();
+    let max_iterations = config.stop.iterations;
+    let progress = match max_iterations {
+        Some(i) if config.progress && !config.events => ProgressBar::new(i as u64),
+        _ => ProgressBar::hidden(),
+    };
+    progress.set_style(ProgressStyle::with_template("{bar:40} {pos}/{len} {msg}").unwrap());
+    let target = config.stop.target;
+    let reached = |value: f64| target.is_some_and(|t| (value - t.target).abs() <= t.tol);
+    let (mut k, mut stagnant, mut best_value) = (0, 0, value);
+    let spent = || evaluations.load(Ordering::Relaxed);
+    // Every sub-swarm evaluates its particles and their personal bests, then updates them
+    let (mut per_iteration, mut mark) = (3 * s * n, spent());
+    let stop_reason = loop {
+        if reached(value) {
+            break StopReason::Target;
+        }
+        if max_iterations.is_some_and(|i| k >= i) {
+            break StopReason::Iterations;
+        }
+        let checks_time = k % TIME_CHECK_INTERVAL == 0;
+        if checks_time && config.stop.time.is_some_and(|t| start.elapsed() >= t) {
+            break StopReason::TimeLimit;
+        }
+        let now = spent();
+        if now > mark {
+            (per_iteration, mark) = (now - mark, now);
+        }
+        if config.stop.evaluations.is_some_and(|budget| now + per_iteration > budget) {
+            break StopReason::EvaluationBudget;
+        }
+        if config.interrupted() {
+            break StopReason::Interrupted;
+        }
+        k += 1;
+
+        let consts = config.policy_at(k);
+        // The sub-swarms value their personal bests again every iteration, this is left
+        if config.reeval_every.is_some_and(|every| k % every == 0) {
+            value = f(&context);
+        }
+        let parts = slices.iter().zip(&bounds).zip(integer.iter().zip(&fixed));
+        for (swarm, ((slice, bounds), (integer, fixed))) in swarms.iter_mut().zip(parts) {
+            let g = |x: &[f64]| in_context(f, &context, slice, x);
+            // The personal bests were valued against the context before the last change
+            swarm.evaluate(&g);
+            update(swarm, &consts, bounds.as_ref(), integer, fixed, &g, opt);
+            if opt.better(swarm.global_value(), value) {
+                value = swarm.global_value();
+                context[slice.clone()].copy_from_slice(&global_slice(swarm));
+            }
+        }
+        if let Some(reason) = failed() {
+            return Err(RunError::ExternalObjective(reason));
+        }
+        let finite = |x: &Vec<F>| x.iter().all(|xi| xi.is_finite());
+        if swarms.iter().any(|swarm| !swarm.position.iter().any(finite)) {
+            return Err(RunError::Diverged);
+        }
+        if config.events {
+            let values = swarms.iter().flat_map(|swarm| &swarm.value);
+            Event::Iteration {
+                i: k,
+                best: value,
+                mean_objective: values.sum::<f64>() / (s * n) as f64,
+                velocity_norm: velocity_norm(&swarms),
+            }
+            .emit();
+        }
+        progress.set_message(format!("best value {}", value));
+        progress.inc(1);
+        debug!(iteration = k, best_value = value, "iteration");
+        trace!(?context);
+
+        if let Some(t) = target {
+            let improvement = match opt {
+                OptimizationPolicy::FindMinimum => best_value - value,
+                OptimizationPolicy::FindMaximum => value - best_value,
+            };
+            if improvement > t.stag_eps {
+                best_value = value;
+                stagnant = 0;
+            } else {
+                stagnant += 1;
+            }
+            if stagnant >= t.stag_window {
+                break StopReason::Stagnation;
+            }
+        }
+    };
+    progress.finish_and_clear();
+    debug!(iterations = k, best_value = value, ?stop_reason, "run finished");
+    if config.events {
+        Event::Done {
+            iterations: k,
+            best: value,
+            stop_reason,
+        }
+        .emit();
+    }
+
+    let elapsed_secs = start.elapsed().as_secs_f64();
+    // Particle i of every sub-swarm together make up particle i of the whole swarm
+    let final_position = (0..n)
+        .map(|i| swarms.iter().flat_map(|swarm| convert::<F, f64>(&swarm.position[i])).collect())
+        .collect::<Vec<Vec<f64>>>();
+    Ok(RunResult {
+        iterations: k,
+        elapsed_secs,
+        iterations_per_sec: if elapsed_secs > 0.0 {
+            k as f64 / elapsed_secs
+        } else {
+            0.0
+        },
+        best_value: value,
+        spread: Spread::of(&final_position, &context, config.conv_radius),
+        best_position: context,
+        seed,
+        converged: !matches!(
+            stop_reason,
+            StopReason::Iterations
+                | StopReason::TimeLimit
+                | StopReason::Interrupted
+                | StopReason::EvaluationBudget
+        ),
+        stop_reason,
+        nan_evaluations: swarms.iter().map(|swarm| swarm.nan_evaluations).sum(),
+        evaluations: 0,
+        final_position,
+        collapsed_at: None,
+        feasible: None,
+        history: None,
+        pre_polish_value: None,
+        position_error: None,
+        value_gap: None,
+        objective_values: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks;
+    use crate::tests::config;
+
+    #[test]
+    fn split_gives_the_remainder_to_the_first_slices() {
+        assert_eq!(split(10, 4), [0..3, 3..6, 6..8, 8..10]);
+        assert_eq!(split(6, 3), [0..2, 2..4, 4..6]);
+        assert_eq!(split(3, 3), [0..1, 1..2, 2..3]);
+    }
+
+    #[test]
+    fn sub_swarms_beat_a_single_swarm_on_a_separable_function() {
+        let best = |cooperative: Option<usize>, seed: u64| {
+            let mut cpso = config(10, 20, benchmarks::rastrigin);
+            cpso.stop.iterations = None;
+            cpso.stop.evaluations = Some(20_000);
+            cpso.cooperative = cooperative;
+            cpso.seed = seed;
+            crate::run(cpso).unwrap().best_value
+        };
+        for seed in 1..=3 {
+            assert!(best(Some(20), seed) < best(None, seed) / 10.0);
+        }
+    }
+}
@@ -0,0 +1,403 @@
+//! A small parser and evaluator for objective functions given as text, e.g. `(x-1)^2 + sin(y)`.
+//!
+//! Variables are `x`, `y`, `z` (coordinates 0, 1, 2) or `x0`, `x1`, ... for any coordinate.
+//! Constraints compare two expressions, e.g. `x + y <= 1`.
+
+use crate::ObjectiveFn;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Function {
+    Sin,
+    Cos,
+    Exp,
+    Log,
+    Sqrt,
+    Abs,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sin" => Some(Function::Sin),
+            "cos" => Some(Function::Cos),
+            "exp" => Some(Function::Exp),
+            "log" => Some(Function::Log),
+            "sqrt" => Some(Function::Sqrt),
+            "abs" => Some(Function::Abs),
+            _ => None,
+        }
+    }
+
+    fn apply(&self, x: f64) -> f64 {
+        match self {
+            Function::Sin => x.sin(),
+            Function::Cos => x.cos(),
+            Function::Exp => x.exp(),
+            Function::Log => x.ln(),
+            Function::Sqrt => x.sqrt(),
+            Function::Abs => x.abs(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Number(f64),
+    Variable(usize),
+    Neg(Box<Node>),
+    Binary(Op, Box<Node>, Box<Node>),
+    Call(Function, Box<Node>),
+}
+
+impl Node {
+    fn eval(&self, x: &[f64]) -> f64 {
+        match self {
+            Node::Number(c) => *c,
+            Node::Variable(i) => x[*i],
+            Node::Neg(a) => -a.eval(x),
+            Node::Binary(op, a, b) => {
+                let (a, b) = (a.eval(x), b.eval(x));
+                match op {
+                    Op::Add => a + b,
+                    Op::Sub => a - b,
+                    Op::Mul => a * b,
+                    Op::Div => a / b,
+                    Op::Pow => a.powf(b),
+                }
+            }
+            Node::Call(func, a) => func.apply(a.eval(x)),
+        }
+    }
+
+    fn dimension(&self) -> usize {
+        match self {
+            Node::Number(_) => 0,
+            Node::Variable(i) => i + 1,
+            Node::Neg(a) | Node::Call(_, a) => a.dimension(),
+            Node::Binary(_, a, b) => a.dimension().max(b.dimension()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Number(c) => write!(f, "{}", c),
+            Token::Ident(name) => write!(f, "{}", name),
+            Token::Op(c) => write!(f, "{}", c),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+        }
+    }
+}
+
+/// The token at which parsing failed
+#[derive(Debug)]
+pub struct ExprError {
+    pub token: String,
+}
+
+impl ExprError {
+    fn at(token: Option<&Token>) -> Self {
+        Self {
+            token: match token {
+                Some(t) => t.to_string(),
+                None => "end of input".to_string(),
+            },
+        }
+    }
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, ExprError> {
+    let chars = src.chars().collect::<Vec<char>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text = chars[start..i].iter().collect::<String>();
+            let value = text.parse::<f64>().map_err(|_| ExprError { token: text })?;
+            tokens.push(Token::Number(value));
+        } else if c.is_ascii_alphabetic() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_alphanumeric() {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            tokens.push(match c {
+                '+' | '-' | '*' | '/' | '^' => Token::Op(c),
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                _ => {
+                    return Err(ExprError {
+                        token: c.to_string(),
+                    })
+                }
+            });
+            i += 1;
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ExprError> {
+        match self.next() {
+            Some(t) if t == expected => Ok(()),
+            t => Err(ExprError::at(t.as_ref())),
+        }
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<Node, ExprError> {
+        let mut lhs = self.term()?;
+        while let Some(Token::Op(c @ ('+' | '-'))) = self.peek() {
+            let op = if *c == '+' { Op::Add } else { Op::Sub };
+            self.pos += 1;
+            lhs = Node::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+        Ok(lhs)
+    }
+
+    // term := unary (('*' | '/') unary)*
+    fn term(&mut self) -> Result<Node, ExprError> {
+        let mut lhs = self.unary()?;
+        while let Some(Token::Op(c @ ('*' | '/'))) = self.peek() {
+            let op = if *c == '*' { Op::Mul } else { Op::Div };
+            self.pos += 1;
+            lhs = Node::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    // unary := '-' unary | power
+    fn unary(&mut self) -> Result<Node, ExprError> {
+        if let Some(Token::Op('-')) = self.peek() {
+            self.pos += 1;
+            return Ok(Node::Neg(Box::new(self.unary()?)));
+        }
+        self.power()
+    }
+
+    // power := atom ('^' unary)?, so that `x^-1` works and `2^3^2` is `2^(3^2)`
+    fn power(&mut self) -> Result<Node, ExprError> {
+        let base = self.atom()?;
+        if let Some(Token::Op('^')) = self.peek() {
+            self.pos += 1;
+            return Ok(Node::Binary(Op::Pow, Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    // atom := number | variable | function '(' expr ')' | '(' expr ')'
+    fn atom(&mut self) -> Result<Node, ExprError> {
+        match self.next() {
+            Some(Token::Number(c)) => Ok(Node::Number(c)),
+            Some(Token::LParen) => {
+                let inner = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Some(Token::Ident(name)) => {
+                if let Some(func) = Function::from_name(&name) {
+                    self.expect(Token::LParen)?;
+                    let arg = self.expr()?;
+                    self.expect(Token::RParen)?;
+                    return Ok(Node::Call(func, Box::new(arg)));
+                }
+                variable(&name)
+                    .map(Node::Variable)
+                    .ok_or(ExprError { token: name })
+            }
+            t => Err(ExprError::at(t.as_ref())),
+        }
+    }
+}
+
+fn variable(name: &str) -> Option<usize> {
+    match name {
+        "x" => Some(0),
+        "y" => Some(1),
+        "z" => Some(2),
+        _ => name.strip_prefix('x')?.parse::<usize>().ok(),
+    }
+}
+
+/// A parsed objective function
+#[derive(Debug, Clone)]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+impl Expression {
+    pub fn parse(src: &str) -> Result<Self, ExprError> {
+        let mut parser = Parser {
+            tokens: tokenize(src)?,
+            pos: 0,
+        };
+        let root = parser.expr()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(ExprError::at(parser.peek()));
+        }
+        Ok(Self {
+            source: src.to_string(),
+            root,
+        })
+    }
+
+    pub fn eval(&self, x: &[f64]) -> f64 {
+        self.root.eval(x)
+    }
+
+    /// The number of coordinates the expression reads, i.e. one past the highest variable index
+    pub fn dimension(&self) -> usize {
+        self.root.dimension()
+    }
+
+    /// The sum of `parts`, each expression multiplied by its weight, to optimize several objectives
+    /// at once
+    pub fn weighted_sum(parts: &[(f64, Expression)]) -> Self {
+        let terms = parts.iter().map(|(w, e)| {
+            let term = Node::Binary(Op::Mul, Box::new(Node::Number(*w)), Box::new(e.root.clone()));
+            (format!("{}*({})", w, e.source), term)
+        });
+        let (sources, terms): (Vec<String>, Vec<Node>) = terms.unzip();
+        let root = terms
+            .into_iter()
+            .reduce(|sum, term| Node::Binary(Op::Add, Box::new(sum), Box::new(term)))
+            .unwrap_or(Node::Number(0.0));
+        Self {
+            source: sources.join(" + "),
+            root,
+        }
+    }
+
+    pub fn into_fn(self) -> ObjectiveFn {
+        Box::new(move |x| self.eval(x))
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// An inequality between two expressions, `lhs <= rhs` or `lhs >= rhs`
+#[derive(Debug, Clone)]
+pub struct Constraint {
+    source: String,
+    /// The smaller side followed by the larger one
+    sides: (Expression, Expression),
+}
+
+impl Constraint {
+    pub fn parse(src: &str) -> Result<Self, ExprError> {
+        let sides = if let Some((lhs, rhs)) = src.split_once("<=") {
+            (Expression::parse(lhs)?, Expression::parse(rhs)?)
+        } else if let Some((lhs, rhs)) = src.split_once(">=") {
+            (Expression::parse(rhs)?, Expression::parse(lhs)?)
+        } else {
+            return Err(ExprError::at(None));
+        };
+        Ok(Self {
+            source: src.to_string(),
+            sides,
+        })
+    }
+
+    /// `g(x)` for the constraint written as `g(x) <= 0`, positive where it is violated
+    pub fn eval(&self, x: &[f64]) -> f64 {
+        self.sides.0.eval(x) - self.sides.1.eval(x)
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.sides.0.dimension().max(self.sides.1.dimension())
+    }
+
+    /// The function `g`
+    pub fn into_fn(self) -> ObjectiveFn {
+        Box::new(move |x| self.eval(x))
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(src: &str, x: &[f64]) -> f64 {
+        Expression::parse(src).unwrap().eval(x)
+    }
+
+    #[test]
+    fn operators_bind_by_precedence() {
+        assert_eq!(eval("-x^2", &[3.0]), -9.0);
+        assert_eq!(eval("2^3^2", &[]), 512.0);
+        assert_eq!(eval("1+2*3", &[]), 7.0);
+    }
+
+    #[test]
+    fn unknown_identifier_is_the_error_token() {
+        let error = Expression::parse("1 + foo * x").unwrap_err();
+        assert_eq!(error.token, "foo");
+    }
+
+    #[test]
+    fn weighted_sum_adds_the_weighted_parts() {
+        let parts = [(2.0, "x^2"), (-0.5, "y - 1"), (3.0, "x*y")];
+        let parts = parts.map(|(w, src)| (w, Expression::parse(src).unwrap()));
+        let sum = Expression::weighted_sum(&parts);
+        let (x, y) = (1.5, -2.0);
+        assert_eq!(sum.eval(&[x, y]), 2.0 * x * x - 0.5 * (y - 1.0) + 3.0 * x * y);
+        assert_eq!(sum.dimension(), 2);
+        assert_eq!(Expression::weighted_sum(&[]).eval(&[]), 0.0);
+    }
+}
@@ -0,0 +1,115 @@
+//! Objective functions computed by an external program, e.g. a simulator written in another
+//! language.
+//!
+//! For every position the program is run through `sh -c`, gets the coordinates on stdin as one
+//! line separated by spaces, and prints the objective value on stdout.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+/// A program evaluated once per distinct position
+pub struct Program {
+    command: String,
+    /// Values by the bits of the coordinates, so that revisited positions spawn no process
+    cache: Mutex<HashMap<Vec<u64>, f64>>,
+    /// The first failure; later evaluations return NaN without running the program
+    error: Mutex<Option<String>>,
+}
+
+impl Program {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            cache: Mutex::new(HashMap::new()),
+            error: Mutex::new(None),
+        }
+    }
+
+    /// The value printed by the program at `x`, or NaN once the program has failed
+    pub fn eval(&self, x: &[f64]) -> f64 {
+        let key = x.iter().map(|xi| xi.to_bits()).collect::<Vec<u64>>();
+        if let Some(&y) = self.cache.lock().unwrap().get(&key) {
+            return y;
+        }
+        if self.error.lock().unwrap().is_some() {
+            return f64::NAN;
+        }
+        match self.spawn(x) {
+            Ok(y) => {
+                self.cache.lock().unwrap().insert(key, y);
+                y
+            }
+            Err(message) => {
+                self.error.lock().unwrap().get_or_insert(message);
+                f64::NAN
+            }
+        }
+    }
+
+    /// Takes the failure of the program, if any, so that the run can stop on it
+    pub fn take_error(&self) -> Option<String> {
+        self.error.lock().unwrap().take()
+    }
+
+    fn spawn(&self, x: &[f64]) -> Result<f64, String> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("could not run {}: {}", self.command, e))?;
+        let line = x
+            .iter()
+            .map(|xi| xi.to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+        // A program that exits without reading its input is judged by its output alone
+        let _ = writeln!(child.stdin.take().unwrap(), "{}", line);
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("could not run {}: {}", self.command, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "{} exited with {} at {}",
+                self.command, output.status, line
+            ));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout.trim().parse::<f64>().map_err(|_| {
+            format!(
+                "{} printed {:?} at {}, expected a number",
+                self.command,
+                stdout.trim(),
+                line
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_prints_the_value() {
+        let program = Program::new("awk '{ print $1 * $1 + 2 * $2 }'");
+        assert_eq!(program.eval(&[3.0, -0.5]), 8.0);
+        // Served from the cache
+        assert_eq!(program.eval(&[3.0, -0.5]), 8.0);
+        assert_eq!(program.cache.lock().unwrap().len(), 1);
+        assert!(program.take_error().is_none());
+    }
+
+    #[test]
+    fn failing_script_gives_nan_and_an_error() {
+        let program = Program::new("exit 3");
+        assert!(program.eval(&[1.0]).is_nan());
+        assert!(program.take_error().unwrap().contains("exited"));
+        let program = Program::new("echo nope");
+        assert!(program.eval(&[1.0]).is_nan());
+        assert!(program.take_error().unwrap().contains("expected a number"));
+    }
+}
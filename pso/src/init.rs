@@ -0,0 +1,178 @@
+//! Initial positions that cover the search space more evenly than independent uniform draws.
+
+use rand::seq::SliceRandom;
+
+/// How the initial positions are drawn from their ranges
+#[derive(Debug, Clone, Copy)]
+pub enum InitMethod {
+    /// Every coordinate independently and uniformly
+    Uniform,
+    /// From the Halton sequence, which fills the ranges without the gaps and clusters of
+    /// independent draws
+    Halton,
+    /// Latin hypercube sampling, with every coordinate of the `n` particles in a different one of
+    /// `n` equal parts of its range
+    LatinHypercube,
+}
+
+/// The first `n` points of the Halton sequence, scaled to `ranges`, one per coordinate
+///
+/// Coordinate `j` is the radical inverse in the `j`-th prime base. Every coordinate is shifted by a
+/// random offset modulo 1 (a Cranley-Patterson rotation), so that different seeds give different
+/// points that are just as evenly spread.
+pub fn halton<R: rand::Rng>(n: usize, ranges: &[(f64, f64)], r: &mut R) -> Vec<Vec<f64>> {
+    let bases = primes(ranges.len());
+    let shifts = bases.iter().map(|_| r.gen::<f64>()).collect::<Vec<f64>>();
+    (1..=n as u64)
+        .map(|i| {
+            bases
+                .iter()
+                .zip(&shifts)
+                .zip(ranges)
+                .map(|((&base, shift), &(lower, upper))| {
+                    let u = (radical_inverse(i, base) + shift).fract();
+                    lower + u * (upper - lower)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// `n` points of a Latin hypercube in `ranges`, one range per coordinate
+///
+/// Each range is split into `n` equal bins, and each coordinate puts one point in every bin at a
+/// uniform position within it, the bins in an order shuffled for every coordinate apart.
+pub fn latin_hypercube<R: rand::Rng>(n: usize, ranges: &[(f64, f64)], r: &mut R) -> Vec<Vec<f64>> {
+    let mut x = vec![Vec::with_capacity(ranges.len()); n];
+    let mut bins = (0..n).collect::<Vec<usize>>();
+    for &(lower, upper) in ranges {
+        bins.shuffle(r);
+        for (xi, &bin) in x.iter_mut().zip(&bins) {
+            let u = (bin as f64 + r.gen::<f64>()) / n as f64;
+            xi.push(lower + u * (upper - lower));
+        }
+    }
+    x
+}
+
+/// The digits of `i` in `base` mirrored around the decimal point, e.g. 6 = 110 in base 2 gives
+/// 0.011 = 0.375
+fn radical_inverse(mut i: u64, base: u64) -> f64 {
+    let (mut value, mut scale) = (0.0, 1.0 / base as f64);
+    while i > 0 {
+        value += (i % base) as f64 * scale;
+        i /= base;
+        scale /= base as f64;
+    }
+    value
+}
+
+/// The first `k` primes
+fn primes(k: usize) -> Vec<u64> {
+    let mut primes = Vec::with_capacity(k);
+    let mut candidate = 2;
+    while primes.len() < k {
+        if primes
+            .iter()
+            .take_while(|&&p| p * p <= candidate)
+            .all(|&p| candidate % p != 0)
+        {
+            primes.push(candidate);
+        }
+        candidate += 1;
+    }
+    primes
+}
+
+/// Most cells that [`grid`] may make, since their number grows as the power of the dimension
+pub const MAX_GRID_CELLS: usize = 1 << 20;
+
+/// Centers of the cells of a grid with `points` equal cells along each of `ranges`, one range per
+/// coordinate, the first coordinate changing fastest
+pub fn grid(points: usize, ranges: &[(f64, f64)]) -> Vec<Vec<f64>> {
+    let cells = points.pow(ranges.len() as u32);
+    (0..cells)
+        .map(|mut i| {
+            let mut center = |&(lower, upper): &(f64, f64)| {
+                let k = i % points;
+                i /= points;
+                lower + (k as f64 + 0.5) * (upper - lower) / points as f64
+            };
+            ranges.iter().map(&mut center).collect()
+        })
+        .collect()
+}
+
+/// The first `k` corners of the box spanned by `ranges`, one range per coordinate, cycling
+/// through the corners once `k` exceeds their number
+///
+/// Corner `i` is at the upper end of coordinate `j` where bit `j` of `i` is set and at the lower
+/// end elsewhere, so the first corner is the lower end of every range and the next ones flip the
+/// first coordinates.
+pub fn corners(k: usize, ranges: &[(f64, f64)]) -> Vec<Vec<f64>> {
+    let d = ranges.len();
+    (0..k)
+        .map(|i| {
+            let i = if d < usize::BITS as usize { i % (1 << d) } else { i };
+            let upper = |j: usize| i.checked_shr(j as u32).is_some_and(|bits| bits & 1 == 1);
+            let corner = ranges.iter().enumerate();
+            corner.map(|(j, &(lower, hi))| if upper(j) { hi } else { lower }).collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Largest gap between the fraction of `x` in a box `[0, a) x [0, b)` of the unit square and
+    /// its area, over a grid of boxes
+    fn discrepancy(x: &[Vec<f64>]) -> f64 {
+        let steps = 64;
+        let mut worst = 0.0f64;
+        for i in 1..=steps {
+            for j in 1..=steps {
+                let (a, b) = (i as f64 / steps as f64, j as f64 / steps as f64);
+                let inside = x.iter().filter(|p| p[0] < a && p[1] < b).count();
+                worst = worst.max((inside as f64 / x.len() as f64 - a * b).abs());
+            }
+        }
+        worst
+    }
+
+    #[test]
+    fn halton_is_more_even_than_uniform() {
+        let ranges = [(0.0, 1.0); 2];
+        for seed in 0..5 {
+            let mut r = StdRng::seed_from_u64(seed);
+            let halton = halton(256, &ranges, &mut r);
+            let uniform = (0..256).map(|_| vec![r.gen(), r.gen()]).collect::<Vec<Vec<f64>>>();
+            assert!(discrepancy(&halton) < discrepancy(&uniform));
+        }
+    }
+
+    #[test]
+    fn latin_hypercube_fills_every_bin_once() {
+        let ranges = [(0.0, 1.0), (-4.0, 6.0), (10.0, 12.0)];
+        let mut r = StdRng::seed_from_u64(2);
+        let x = latin_hypercube(20, &ranges, &mut r);
+        assert_eq!(x.len(), 20);
+        for (j, &(lower, upper)) in ranges.iter().enumerate() {
+            let mut bins = x
+                .iter()
+                .map(|xi| ((xi[j] - lower) / (upper - lower) * 20.0).floor() as usize)
+                .collect::<Vec<usize>>();
+            bins.sort_unstable();
+            assert_eq!(bins, (0..20).collect::<Vec<usize>>());
+        }
+    }
+
+    #[test]
+    fn corners_flip_the_first_coordinates_first() {
+        let ranges = [(-1.0, 1.0), (2.0, 5.0)];
+        let expected = [[-1.0, 2.0], [1.0, 2.0], [-1.0, 5.0], [1.0, 5.0], [-1.0, 2.0]];
+        assert_eq!(corners(5, &ranges), expected);
+    }
+}
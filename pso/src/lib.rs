@@ -0,0 +1,3473 @@
+//! Particle Swarm Optimization over objective functions of any dimension.
+//!
+//! Build a [`PsoConfig`] and pass it to [`run`], or drive a [`ParticleSwarm`] with [`update`]
+//! directly.
+
+pub mod ascii;
+pub mod benchmarks;
+pub mod cooperative;
+pub mod expr;
+pub mod external;
+pub mod init;
+#[cfg(feature = "plotters")]
+pub mod plot;
+pub mod polish;
+
+use indicatif::{ProgressBar, ProgressStyle};
+use init::InitMethod;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use rand::distributions::uniform::SampleUniform;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::iter::Sum;
+use std::mem;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{self, AtomicBool, AtomicUsize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info_span, trace};
+
+/// An objective function, `Sync` so that particles can be evaluated in parallel
+pub type ObjectiveFn = Box<dyn Fn(&[f64]) -> f64 + Sync>;
+
+/// Observer of every iteration, see [`PsoConfig::on_iteration`]
+pub type IterationCallback = Box<dyn FnMut(&IterationStats) -> ControlFlow<()>>;
+
+/// Type of the coordinates of positions and velocities, `f32` taking half the memory of `f64`;
+/// objective values, coefficients and results are `f64` either way
+pub trait Scalar:
+    num_traits::Float
+    + num_traits::NumAssign
+    + SampleUniform
+    + Sum
+    + fmt::Debug
+    + fmt::Display
+    + Send
+    + Sync
+{
+    fn of(x: f64) -> Self;
+    fn widen(self) -> f64;
+
+    /// The objective at `x`, copied into `f64` coordinates unless it has them already
+    fn eval(f: &(dyn Fn(&[f64]) -> f64 + Sync), x: &[Self]) -> f64;
+}
+
+impl Scalar for f64 {
+    fn of(x: f64) -> Self {
+        x
+    }
+
+    fn widen(self) -> f64 {
+        self
+    }
+
+    fn eval(f: &(dyn Fn(&[f64]) -> f64 + Sync), x: &[Self]) -> f64 {
+        f(x)
+    }
+}
+
+impl Scalar for f32 {
+    fn of(x: f64) -> Self {
+        x as f32
+    }
+
+    fn widen(self) -> f64 {
+        self as f64
+    }
+
+    fn eval(f: &(dyn Fn(&[f64]) -> f64 + Sync), x: &[Self]) -> f64 {
+        f(&convert::<f32, f64>(x))
+    }
+}
+
+/// Converts coordinates between precisions
+fn convert<F: Scalar, G: Scalar>(x: &[F]) -> Vec<G> {
+    x.iter().map(|&xi| G::of(xi.widen())).collect()
+}
+
+/// Which [`Scalar`] a run computes in
+#[derive(Debug, Clone, Copy)]
+pub enum Precision {
+    F64,
+    F32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticleSwarm<F: Scalar = f64> {
+    pub position: Vec<Vec<F>>,
+    pub velocity: Vec<Vec<F>>,
+    pub local_optimum: Vec<Vec<F>>,
+    /// Objective value at every position, evaluated once whenever the position moves
+    #[serde(skip)]
+    pub value: Vec<f64>,
+    /// Objective value at every personal best
+    #[serde(skip)]
+    pub local_value: Vec<f64>,
+    pub neighborhood_best: Vec<Vec<F>>,
+    pub global_optimum: Option<Vec<F>>,
+    pub best_index: usize,
+    /// Best position ever seen and its value, which never gets worse even if the global optimum
+    /// does
+    pub best_ever: (Vec<F>, f64),
+    /// Number of objective evaluations of particle positions that returned NaN
+    pub nan_evaluations: usize,
+    /// Iterations since every particle last improved its personal best
+    pub stale: Vec<usize>,
+    /// Step size of the best particle with `VelocityModel::Gcpso`
+    pub radius: SearchRadius,
+    /// Random stream of every particle, so that updates do not depend on the order particles are
+    /// visited in; ChaCha12 is the generator behind `StdRng`, but its state can be saved
+    pub rngs: Vec<ChaCha12Rng>,
+    /// Weight of every particle in the choice of the global best, see
+    /// [`OptimizationPolicy::weigh`]; empty for equal weights
+    #[serde(skip)]
+    pub weights: Vec<f64>,
+}
+
+impl<F: Scalar> ParticleSwarm<F> {
+    pub fn new(
+        n: usize,
+        d: usize,
+        x: Vec<Vec<F>>,
+        v: Vec<Vec<F>>,
+        f: &(dyn Fn(&[f64]) -> f64 + Sync),
+        opt: &OptimizationPolicy,
+    ) -> Self {
+        assert!(x.len() == n, "Position vector must have length equal to number of particles");
+        assert!(v.len() == n, "Velocity vector must have length equal to number of particles");
+        assert!(
+            x.iter().all(|p| p.len() == d),
+            "Every particle position must have length equal to the dimension"
+        );
+        assert!(
+            v.iter().all(|p| p.len() == d),
+            "Every particle velocity must have length equal to the dimension"
+        );
+
+        let value = x.iter().map(|x| F::eval(f, x)).collect();
+        Self::evaluated(n, d, x, v, value, opt)
+    }
+
+    /// The swarm of [`ParticleSwarm::new`] with the objective values of `x` already known
+    fn evaluated(
+        n: usize,
+        d: usize,
+        x: Vec<Vec<F>>,
+        v: Vec<Vec<F>>,
+        value: Vec<f64>,
+        opt: &OptimizationPolicy,
+    ) -> Self {
+        debug_assert!(value.len() == n && x.iter().all(|p| p.len() == d));
+        let local_optimum = x.clone();
+        let best_index = best_of(&value, &[], opt);
+        let global_optimum = local_optimum[best_index].clone();
+        let best_ever = (global_optimum.clone(), value[best_index]);
+        let nan_evaluations = value.iter().filter(|y| y.is_nan()).count();
+
+        Self {
+            position: x,
+            velocity: v,
+            global_optimum: Some(global_optimum),
+            best_index,
+            best_ever,
+            nan_evaluations,
+            neighborhood_best: local_optimum.clone(),
+            local_optimum,
+            local_value: value.clone(),
+            value,
+            stale: vec![0; n],
+            radius: SearchRadius::default(),
+            rngs: particle_rngs(0, n),
+            weights: Vec::new(),
+        }
+    }
+    /// Draws coordinate `j` of every position uniformly from `x_ranges[j]`, and every velocity
+    /// coordinate from `v_range`
+    pub fn new_random<R: rand::Rng>(
+        n: usize,
+        d: usize,
+        x_ranges: &[(f64, f64)],
+        v_range: (f64, f64),
+        f: &(dyn Fn(&[f64]) -> f64 + Sync),
+        opt: &OptimizationPolicy,
+        r: &mut R,
+    ) -> Self {
+        assert!(x_ranges.len() == d, "There must be one position range per coordinate");
+        let (position, velocity) = random_start(n, x_ranges, v_range, r);
+        let mut swarm = Self::new(n, d, position, velocity, f, opt);
+        swarm.rngs = particle_rngs(r.gen(), n);
+        swarm
+    }
+
+    /// Objective value of the global best
+    pub fn global_value(&self) -> f64 {
+        self.local_value[self.best_index]
+    }
+
+    /// Statistics of the current state, from the objective values already known
+    pub fn summary(&self) -> SwarmSummary {
+        let n = self.position.len() as f64;
+        let d = self.local_optimum[self.best_index].len();
+        let coordinate = |j: usize| self.position.iter().map(move |x| x[j].widen());
+        let extent = |j: usize| {
+            coordinate(j).fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), xj| {
+                (lo.min(xj), hi.max(xj))
+            })
+        };
+        let (min, max) = (0..d).map(extent).unzip();
+        let speed = |j: usize| self.velocity.iter().map(|v| v[j].widen().abs()).sum::<f64>() / n;
+        let norm = |v: &Vec<F>| v.iter().map(|&vi| vi * vi).sum::<F>().sqrt().widen();
+        SwarmSummary {
+            best_position: convert(&self.local_optimum[self.best_index]),
+            best_value: self.global_value(),
+            best_index: self.best_index,
+            min,
+            mean: (0..d).map(|j| coordinate(j).sum::<f64>() / n).collect(),
+            max,
+            mean_speed: (0..d).map(speed).collect(),
+            mean_objective: self.value.iter().sum::<f64>() / n,
+            velocity_norm: self.velocity.iter().map(norm).sum::<f64>() / n,
+            diameter: diameter(&self.position),
+        }
+    }
+
+    /// Recomputes the objective values, which are not saved with the swarm
+    pub fn evaluate(&mut self, f: &(dyn Fn(&[f64]) -> f64 + Sync)) {
+        self.value = self.position.iter().map(|x| F::eval(f, x)).collect();
+        self.local_value = self.local_optimum.iter().map(|x| F::eval(f, x)).collect();
+    }
+
+    /// The same swarm in another precision, e.g. to save an `f32` swarm in a [`Checkpoint`]
+    pub fn cast<G: Scalar>(&self) -> ParticleSwarm<G> {
+        let rows = |x: &[Vec<F>]| x.iter().map(|xi| convert(xi)).collect();
+        ParticleSwarm {
+            position: rows(&self.position),
+            velocity: rows(&self.velocity),
+            local_optimum: rows(&self.local_optimum),
+            value: self.value.clone(),
+            local_value: self.local_value.clone(),
+            neighborhood_best: rows(&self.neighborhood_best),
+            global_optimum: self.global_optimum.as_deref().map(convert),
+            best_index: self.best_index,
+            best_ever: (convert(&self.best_ever.0), self.best_ever.1),
+            nan_evaluations: self.nan_evaluations,
+            stale: self.stale.clone(),
+            radius: self.radius,
+            rngs: self.rngs.clone(),
+            weights: self.weights.clone(),
+        }
+    }
+
+    /// Evaluates the personal bests and the best ever position again, keeping the positions but not
+    /// the values they had, which a noisy objective may have drawn luckily; with `elitism` the best
+    /// ever keeps its value unless the new one is better, and stays the global attractor, see
+    /// [`UpdatePolicy::elitism`]
+    pub fn reevaluate_bests(
+        &mut self,
+        f: &(dyn Fn(&[f64]) -> f64 + Sync),
+        opt: &OptimizationPolicy,
+        elitism: bool,
+    ) {
+        self.local_value = self.local_optimum.iter().map(|x| F::eval(f, x)).collect();
+        self.best_index = best_of(&self.local_value, &self.weights, opt);
+        let global_optimum = &self.local_optimum[self.best_index];
+        self.global_optimum = Some(global_optimum.clone());
+        let value = F::eval(f, &self.best_ever.0);
+        if !elitism || opt.better(value, self.best_ever.1) {
+            self.best_ever.1 = value;
+        }
+        if opt.better(self.global_value(), self.best_ever.1) {
+            self.best_ever = (global_optimum.clone(), self.global_value());
+        }
+        keep_elite(self, opt, elitism);
+    }
+
+    /// Weighs the particles in the choice of the global best, one weight each, and chooses it again
+    pub fn set_weights(&mut self, weights: Vec<f64>, opt: &OptimizationPolicy) {
+        self.weights = weights;
+        self.best_index = best_of(&self.local_value, &self.weights, opt);
+        self.global_optimum = Some(self.local_optimum[self.best_index].clone());
+    }
+
+    /// Restarts the random stream of every particle from `seed`
+    pub fn seed_particles(&mut self, seed: u64) {
+        self.rngs = particle_rngs(seed, self.position.len());
+    }
+
+    /// Makes the next [`update`] draw every particle anew as with [`UpdatePolicy::reinit_after`],
+    /// keeping the personal bests
+    pub fn scatter(&mut self) {
+        self.stale.fill(usize::MAX);
+    }
+}
+
+/// Index of the best of `values`, each weighed by its entry in `weights` unless that is empty, the
+/// first one among equals
+fn best_of(values: &[f64], weights: &[f64], opt: &OptimizationPolicy) -> usize {
+    let weigh = |(i, y): (usize, f64)| (i, weights.get(i).map_or(y, |&w| opt.weigh(y, w)));
+    let indexed = values.iter().copied().enumerate().map(weigh);
+    indexed.max_by(|&a, &b| opt.compare_indexed(a, b)).unwrap().0
+}
+
+/// The generator of particle `i` is seeded with `seed ^ i`
+/// Positions and velocities of `n` particles as drawn by [`ParticleSwarm::new_random`]
+fn random_start<F: Scalar, R: rand::Rng>(
+    n: usize,
+    x_ranges: &[(f64, f64)],
+    v_range: (f64, f64),
+    r: &mut R,
+) -> (Vec<Vec<F>>, Vec<Vec<F>>) {
+    let mut position = Vec::with_capacity(n);
+    let mut velocity = Vec::with_capacity(n);
+    for _ in 0..n {
+        let mut x = Vec::with_capacity(x_ranges.len());
+        let mut v = Vec::with_capacity(x_ranges.len());
+        for &(lower, upper) in x_ranges {
+            x.push(r.gen_range(F::of(lower)..=F::of(upper)));
+            v.push(r.gen_range(F::of(v_range.0)..=F::of(v_range.1)));
+        }
+        position.push(x);
+        velocity.push(v);
+    }
+    (position, velocity)
+}
+
+fn particle_rngs(seed: u64, n: usize) -> Vec<ChaCha12Rng> {
+    (0..n).map(|i| ChaCha12Rng::seed_from_u64(seed ^ i as u64)).collect()
+}
+
+/// Consecutive improvements of the global best after which GCPSO doubles its search radius
+pub const GCPSO_SUCCESSES: usize = 15;
+/// Consecutive iterations without improvement after which GCPSO halves its search radius
+pub const GCPSO_FAILURES: usize = 5;
+
+/// Side `rho` of the random box that GCPSO's best particle samples around the global best, grown
+/// while it keeps finding better positions and shrunk while it does not
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SearchRadius {
+    pub rho: f64,
+    pub successes: usize,
+    pub failures: usize,
+}
+
+impl Default for SearchRadius {
+    fn default() -> Self {
+        Self {
+            rho: 1.0,
+            successes: 0,
+            failures: 0,
+        }
+    }
+}
+
+impl SearchRadius {
+    /// Counts an iteration in which the global best did or did not improve, adapting `rho` once
+    /// either streak exceeds its threshold
+    fn adapt(&mut self, improved: bool) {
+        if improved {
+            self.successes += 1;
+            self.failures = 0;
+        } else {
+            self.failures += 1;
+            self.successes = 0;
+        }
+        if self.successes > GCPSO_SUCCESSES {
+            self.rho *= 2.0;
+        } else if self.failures > GCPSO_FAILURES {
+            self.rho *= 0.5;
+        }
+    }
+}
+
+/// The global best and, per coordinate, the range and mean of the positions and the mean speed,
+/// one line each; the alternate form `{:#}` lists every position and velocity instead
+impl<F: Scalar> fmt::Display for ParticleSwarm<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // A precision, as in `{:.3}`, applies to every number, which are otherwise printed in full
+        // and the table with 5 digits
+        let digits = f.precision();
+        if f.alternate() {
+            let (x, v) = (&self.position, &self.velocity);
+            return match digits {
+                Some(p) => write!(f, "Positions: {:.*?}\nVelocities: {:.*?}", p, x, p, v),
+                None => write!(f, "Positions: {:?}\nVelocities: {:?}", x, v),
+            };
+        }
+        let s = self.summary();
+        let (value, x) = (s.best_value, &s.best_position);
+        match digits {
+            Some(p) => write!(f, "Global best: {:.*} at {:.*?}", p, value, p, x),
+            None => write!(f, "Global best: {} at {:?}", value, x),
+        }?;
+        // Room for the sign, the first digit, the point and an exponent of two digits
+        let p = digits.unwrap_or(5);
+        let w = p + 7;
+        write!(
+            f,
+            "\n{:>10}  {:>w$}  {:>w$}  {:>w$}  {:>w$}",
+            "coordinate", "min", "mean", "max", "mean |v|"
+        )?;
+        for j in 0..s.best_position.len() {
+            let (min, mean, max, speed) = (s.min[j], s.mean[j], s.max[j], s.mean_speed[j]);
+            let row = [min, mean, max, speed].map(|x| format!("{:>w$.p$e}", x)).join("  ");
+            write!(f, "\n{:>10}  {}", j, row)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct UpdatePolicy {
+    pub w: f64,
+    pub c1: f64,
+    pub c2: f64,
+    pub vmax: Option<f64>,
+    pub topology: Topology,
+    pub model: VelocityModel,
+    /// Probability per particle and iteration of adding Gaussian noise to its velocity
+    pub mutation: f64,
+    /// Standard deviation of the mutation noise
+    pub mutation_scale: f64,
+    /// Draw a particle anew within the bounds and stop it, keeping its personal best, once its
+    /// personal best has not improved for this many iterations
+    pub reinit_after: Option<usize>,
+    /// Charge `q` of the charged particles with `VelocityModel::Charged`, each receiving an
+    /// acceleration of `q / r^2` away from every other charged particle at distance `r`
+    pub charge: f64,
+    /// Put the best position ever seen back as the personal best of the best particle whenever
+    /// that is worse, and keep its value when evaluated again, so that the global best never gets
+    /// worse even when [`ParticleSwarm::reevaluate_bests`] finds the personal bests worse under
+    /// noise
+    pub elitism: bool,
+    /// Pull every particle towards its personal best; off, the term `c1 * r1 * (local - x)` is
+    /// left out of the velocity, while `c1` still counts towards the constriction coefficient
+    pub cognitive: bool,
+    /// Pull every particle towards the best of its neighborhood; off, the term
+    /// `c2 * r2 * (social - x)` is left out likewise
+    pub social: bool,
+    /// Pair every odd particle with the one before it, drawing its `r1` and `r2` as `1 - r` of
+    /// the partner's to take out some of the variance between their updates; with an odd number
+    /// of particles the last one draws alone
+    pub antithetic: bool,
+}
+
+impl UpdatePolicy {
+    pub fn new(w: f64, c1: f64, c2: f64) -> Self {
+        Self {
+            w,
+            c1,
+            c2,
+            vmax: None,
+            topology: Topology::Gbest,
+            model: VelocityModel::Inertia,
+            mutation: 0.0,
+            mutation_scale: 0.1,
+            reinit_after: None,
+            charge: 1.0,
+            elitism: false,
+            cognitive: true,
+            social: true,
+            antithetic: false,
+        }
+    }
+
+    /// Clerc's constriction coefficient, defined for `c1 + c2 > 4`
+    pub fn constriction(&self) -> f64 {
+        let phi = self.c1 + self.c2;
+        2.0 / (2.0 - phi - (phi * phi - 4.0 * phi).sqrt()).abs()
+    }
+}
+
+/// A coefficient that changes linearly from `start` at the first iteration to `end` at the last
+#[derive(Debug, Clone, Copy)]
+pub struct LinearSchedule {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl LinearSchedule {
+    /// The value at iteration `k` of `total`, counting from 1
+    pub fn at(&self, k: usize, total: usize) -> f64 {
+        if total <= 1 {
+            return self.start;
+        }
+        let t = (k - 1) as f64 / (total - 1) as f64;
+        self.start + t * (self.end - self.start)
+    }
+}
+
+/// Schedules of both acceleration coefficients
+#[derive(Debug, Clone, Copy)]
+pub struct AccelerationSchedule {
+    pub c1: LinearSchedule,
+    pub c2: LinearSchedule,
+}
+
+impl AccelerationSchedule {
+    /// Time-varying acceleration coefficients of Ratnaweera et al.: the cognitive term dominates
+    /// early to explore, the social term late to converge
+    pub fn tvac() -> Self {
+        Self {
+            c1: LinearSchedule { start: 2.5, end: 0.5 },
+            c2: LinearSchedule { start: 0.5, end: 2.5 },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum VelocityModel {
+    /// The previous velocity is damped by the inertia weight `w`
+    Inertia,
+    /// The whole velocity update is scaled by the constriction coefficient, ignoring `w`
+    Constriction,
+    /// No velocity at all: every position is drawn anew within the bounds, or `[-1, 1]` without
+    /// bounds, each iteration, as a baseline to compare against
+    RandomSearch,
+    /// Mendes' fully informed swarm: constricted like `Constriction`, but pulled towards the
+    /// personal bests of the particle and all its neighbors alike, with `c1 + c2` shared among them
+    Fips,
+    /// Kennedy's bare-bones swarm: every coordinate is drawn from a Gaussian centered halfway
+    /// between the personal and the neighborhood best, with their distance as standard deviation.
+    /// Velocities are left as initialized and unused, so `w`, `c1`, `c2` and `vmax` have no effect
+    BareBones,
+    /// Blackwell and Bentley's charged swarm: like `Inertia`, but the particles of even index carry
+    /// the charge of [`UpdatePolicy::charge`] and are pushed apart from each other, so that they
+    /// keep exploring while the neutral half converges
+    Charged,
+    /// Van den Bergh's guaranteed convergence PSO: like `Inertia`, but the particle holding the
+    /// global best is moved to it plus its damped velocity and a random step within the
+    /// [`SearchRadius`], so that it keeps searching even where its velocity would vanish; that
+    /// particle is neither mutated nor held to `vmax`
+    Gcpso,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Topology {
+    /// Every particle is attracted to the best position found by the whole swarm
+    Gbest,
+    /// Every particle is attracted to the best position among its `k` nearest neighbors by index
+    Ring { k: usize },
+    /// Particles sit on a near-square grid, filled row by row, and every particle is attracted to
+    /// the best among its neighbors above, below, left and right, wrapping around the edges
+    VonNeumann,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum BoundMode {
+    Clamp,
+    Reflect,
+    /// Periodic, e.g. an angle: a particle leaving through one end comes back in through the other
+    /// at the same distance, with the same velocity
+    Wrap,
+}
+
+/// A box with one `[lower, upper]` interval per coordinate
+#[derive(Debug, Clone)]
+pub struct Bounds {
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+    /// How each coordinate is kept inside its interval
+    pub modes: Vec<BoundMode>,
+}
+
+impl Bounds {
+    /// The same interval and mode for each of the `d` coordinates
+    pub fn uniform(lower: f64, upper: f64, d: usize, mode: BoundMode) -> Self {
+        Self {
+            lower: vec![lower; d],
+            upper: vec![upper; d],
+            modes: vec![mode; d],
+        }
+    }
+
+    /// The interval of coordinate `j`
+    pub fn range(&self, j: usize) -> (f64, f64) {
+        (self.lower[j], self.upper[j])
+    }
+
+    /// Moves coordinate `j` back into its interval if it left it
+    pub fn apply<F: Scalar>(&self, j: usize, x: &mut F, v: &mut F) {
+        let (lower, upper) = self.range(j);
+        let (lower, upper) = (F::of(lower), F::of(upper));
+        if *x >= lower && *x <= upper {
+            return;
+        }
+        match self.modes[j] {
+            BoundMode::Clamp => {
+                *x = x.clamp(lower, upper);
+            }
+            BoundMode::Reflect => {
+                let two = F::of(2.0);
+                if *x < lower {
+                    *x = two * lower - *x;
+                } else {
+                    *x = two * upper - *x;
+                }
+                // A step longer than the box itself can still overshoot
+                *x = x.clamp(lower, upper);
+                *v = -*v;
+            }
+            BoundMode::Wrap => {
+                let width = upper - lower;
+                let mut offset = (*x - lower) % width;
+                if offset < F::zero() {
+                    offset += width;
+                }
+                // Rounding may land a hair outside on the way back
+                *x = (lower + offset).clamp(lower, upper);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum OptimizationPolicy {
+    FindMinimum,
+    FindMaximum,
+}
+
+impl OptimizationPolicy {
+    /// Orders objective values so that the better one is greater, with NaN worse than anything
+    pub fn compare(&self, a: f64, b: f64) -> Ordering {
+        match (a.is_nan(), b.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => match self {
+                OptimizationPolicy::FindMinimum => b.partial_cmp(&a).unwrap(),
+                OptimizationPolicy::FindMaximum => a.partial_cmp(&b).unwrap(),
+            },
+        }
+    }
+
+    /// Whether `a` is strictly better than `b`
+    pub fn better(&self, a: f64, b: f64) -> bool {
+        self.compare(a, b) == Ordering::Greater
+    }
+
+    /// Orders the values of particles `i` and `j` as [`compare`](Self::compare), with the lower
+    /// index better among equal values, so that the best of a swarm does not depend on the order
+    /// the particles are visited in
+    pub fn compare_indexed(&self, (i, a): (usize, f64), (j, b): (usize, f64)) -> Ordering {
+        self.compare(a, b).then(j.cmp(&i))
+    }
+
+    /// The value `y` of a particle of weight `w` as it competes for the global best, moved towards
+    /// the better end by a factor of `w`: `y / w` for a positive value and `y * w` for a negative
+    /// one when minimizing, the other way around when maximizing, so that a heavier particle counts
+    /// more whatever the sign of its value
+    pub fn weigh(&self, y: f64, w: f64) -> f64 {
+        let better = match self {
+            OptimizationPolicy::FindMinimum => y < 0.0,
+            OptimizationPolicy::FindMaximum => y >= 0.0,
+        };
+        if better {
+            y * w
+        } else {
+            y / w
+        }
+    }
+}
+
+/// Mean magnitude over the particles of the two attractions of a velocity update, zero for the
+/// models without velocities
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pull {
+    /// Of `c1 * r1 * (local - x)`, towards the particle's own best
+    pub cognitive: f64,
+    /// Of `c2 * r2 * (social - x)`, towards the best of the neighborhood; with FIPS the pull of
+    /// all the neighbors
+    pub social: f64,
+}
+
+/// Moves every particle one step; coordinates listed in `integer` are rounded after the move, while
+/// their velocities stay continuous, and coordinates listed in `fixed` stay at their value at rest
+pub fn update<F: Scalar>(
+    swarm: &mut ParticleSwarm<F>,
+    consts: &UpdatePolicy,
+    bounds: Option<&Bounds>,
+    integer: &[usize],
+    fixed: &[(usize, f64)],
+    f: &(dyn Fn(&[f64]) -> f64 + Sync),
+    opt: &OptimizationPolicy,
+) -> Pull {
+    // Update the particle's position, keeping it inside the search space
+    let random_search = matches!(consts.model, VelocityModel::RandomSearch);
+    let bare_bones = matches!(consts.model, VelocityModel::BareBones);
+    let n = swarm.position.len();
+    let (local_optimum, local_value) = (&swarm.local_optimum, &swarm.local_value);
+    let global_optimum = swarm.global_optimum.as_ref().unwrap();
+    let reinit_after = consts.reinit_after.unwrap_or(usize::MAX);
+    #[cfg(not(feature = "rayon"))]
+    let particles = swarm
+        .position
+        .iter_mut()
+        .zip(swarm.velocity.iter_mut())
+        .zip(swarm.rngs.iter_mut().zip(swarm.stale.iter_mut()));
+    #[cfg(feature = "rayon")]
+    let particles = swarm
+        .position
+        .par_iter_mut()
+        .zip(swarm.velocity.par_iter_mut())
+        .zip(swarm.rngs.par_iter_mut().zip(swarm.stale.par_iter_mut()));
+    particles.enumerate().for_each(|(i, ((x, v), (r, stale)))| {
+        if *stale >= reinit_after {
+            for (j, (xj, vj)) in x.iter_mut().zip(v.iter_mut()).enumerate() {
+                let (lower, upper) = bounds.map_or((-1.0, 1.0), |b| b.range(j));
+                *xj = rand::Rng::gen_range(r, F::of(lower)..=F::of(upper));
+                *vj = F::zero();
+            }
+            *stale = 0;
+        } else if random_search {
+            for (j, xj) in x.iter_mut().enumerate() {
+                let (lower, upper) = bounds.map_or((-1.0, 1.0), |b| b.range(j));
+                *xj = rand::Rng::gen_range(r, F::of(lower)..=F::of(upper));
+            }
+        } else if bare_bones {
+            // The neighborhood best is looked up here, since it is only refreshed further down
+            let social = match consts.topology {
+                Topology::Gbest => global_optimum,
+                Topology::Ring { .. } | Topology::VonNeumann => {
+                    &local_optimum[best_neighbor(&consts.topology, i, n, local_value, opt)]
+                }
+            };
+            let best = &local_optimum[i];
+            for (j, (xj, vj)) in x.iter_mut().zip(v.iter_mut()).enumerate() {
+                let mid = (best[j] + social[j]) / F::of(2.0);
+                *xj = mid + (best[j] - social[j]).abs() * F::of(gaussian(r));
+                if let Some(b) = bounds {
+                    b.apply(j, xj, vj);
+                }
+            }
+        } else {
+            for (j, (xj, vj)) in x.iter_mut().zip(v.iter_mut()).enumerate() {
+                *xj += *vj;
+                if let Some(b) = bounds {
+                    b.apply(j, xj, vj);
+                }
+            }
+        }
+        round_integers(x, integer);
+        pin(x, v, fixed);
+    });
+
+    // Update the particle's best position, evaluating every particle once where it moved to
+    #[cfg(not(feature = "rayon"))]
+    let particles = swarm
+        .position
+        .iter()
+        .zip(swarm.value.iter_mut())
+        .zip(swarm.local_optimum.iter_mut().zip(swarm.local_value.iter_mut()))
+        .zip(swarm.stale.iter_mut());
+    #[cfg(feature = "rayon")]
+    let particles = swarm
+        .position
+        .par_iter()
+        .zip(swarm.value.par_iter_mut())
+        .zip(swarm.local_optimum.par_iter_mut().zip(swarm.local_value.par_iter_mut()))
+        .zip(swarm.stale.par_iter_mut());
+    let nan_evaluations = particles
+        .map(|(((x, value), (best, best_value)), stale)| {
+            *value = F::eval(f, x);
+            if opt.better(*value, *best_value) {
+                best.clone_from(x);
+                *best_value = *value;
+                *stale = 0;
+            } else {
+                *stale += 1;
+            }
+            value.is_nan() as usize
+        })
+        .sum::<usize>();
+    swarm.nan_evaluations += nan_evaluations;
+
+    // Update the swarm's global best position
+    let previous = swarm.global_value();
+    swarm.best_index = best_of(&swarm.local_value, &swarm.weights, opt);
+    let global_optimum = &swarm.local_optimum[swarm.best_index];
+    swarm.global_optimum = Some(global_optimum.clone());
+    let value = swarm.global_value();
+    if opt.better(value, swarm.best_ever.1) {
+        swarm.best_ever = (global_optimum.clone(), value);
+    }
+    keep_elite(swarm, opt, consts.elitism);
+    if let VelocityModel::Gcpso = consts.model {
+        swarm.radius.adapt(opt.better(value, previous));
+    }
+
+    // Update the particle's neighborhood best
+    if let Topology::Ring { .. } | Topology::VonNeumann = consts.topology {
+        let (local_optimum, local_value) = (&swarm.local_optimum, &swarm.local_value);
+        #[cfg(not(feature = "rayon"))]
+        let neighborhoods = swarm.neighborhood_best.iter_mut().enumerate();
+        #[cfg(feature = "rayon")]
+        let neighborhoods = swarm.neighborhood_best.par_iter_mut().enumerate();
+        neighborhoods.for_each(|(i, neighborhood_best)| {
+            let best = best_neighbor(&consts.topology, i, n, local_value, opt);
+            neighborhood_best.clone_from(&local_optimum[best]);
+        });
+    }
+
+    // Update the particle's velocity, pulling each coordinate separately
+    if random_search || bare_bones {
+        return Pull::default();
+    }
+    let global_optimum = swarm.global_optimum.as_ref().unwrap();
+    let (best_index, rho) = (swarm.best_index, swarm.radius.rho);
+    let social = |i: usize| match consts.topology {
+        Topology::Gbest => global_optimum,
+        Topology::Ring { .. } | Topology::VonNeumann => &swarm.neighborhood_best[i],
+    };
+    // The streams of the partners before they draw, to mirror their draws
+    let partners = consts.antithetic.then(|| swarm.rngs.clone());
+    #[cfg(not(feature = "rayon"))]
+    let particles = swarm.velocity.iter_mut().zip(swarm.rngs.iter_mut()).enumerate();
+    #[cfg(feature = "rayon")]
+    let particles = swarm.velocity.par_iter_mut().zip(swarm.rngs.par_iter_mut()).enumerate();
+    let pulls = particles
+        .map(|(i, (v, r))| {
+            let (x, best) = (&swarm.position[i], &swarm.local_optimum[i]);
+            let pull = match consts.model {
+                VelocityModel::Fips => {
+                    let informants = std::iter::once(i)
+                        .chain(neighbors(&consts.topology, i, n))
+                        .map(|k| &swarm.local_optimum[k][..])
+                        .collect::<Vec<&[F]>>();
+                    fully_informed(v, x, &informants, consts, r)
+                }
+                VelocityModel::Gcpso if i == best_index => {
+                    guaranteed(v, x, global_optimum, consts.w, rho, r);
+                    Pull::default()
+                }
+                _ => match partners.as_ref().filter(|_| i % 2 == 1) {
+                    Some(partners) => {
+                        let mut partner = partners[i - 1].clone();
+                        let draw = || 1.0 - rand::Rng::gen::<f64>(&mut partner);
+                        accelerate(v, x, best, social(i), consts, draw)
+                    }
+                    None => accelerate(v, x, best, social(i), consts, || rand::Rng::gen(r)),
+                },
+            };
+            if matches!(consts.model, VelocityModel::Charged) && i % 2 == 0 {
+                repel(v, i, &swarm.position, consts.charge);
+            }
+            // The step of GCPSO's best particle is bounded by the search radius instead
+            if !(matches!(consts.model, VelocityModel::Gcpso) && i == best_index) {
+                perturb(v, consts, r);
+            }
+            for &(j, _) in fixed {
+                v[j] = F::zero();
+            }
+            pull
+        })
+        .collect::<Vec<Pull>>();
+    Pull {
+        cognitive: pulls.iter().map(|p| p.cognitive).sum::<f64>() / n as f64,
+        social: pulls.iter().map(|p| p.social).sum::<f64>() / n as f64,
+    }
+}
+
+/// Puts the best position ever seen back as the personal best of the best particle with
+/// `elitism`, if that is worse, making it the global attractor again
+fn keep_elite<F: Scalar>(swarm: &mut ParticleSwarm<F>, opt: &OptimizationPolicy, elitism: bool) {
+    if elitism && opt.better(swarm.best_ever.1, swarm.global_value()) {
+        let i = swarm.best_index;
+        swarm.local_optimum[i].clone_from(&swarm.best_ever.0);
+        swarm.local_value[i] = swarm.best_ever.1;
+        swarm.global_optimum = Some(swarm.best_ever.0.clone());
+    }
+}
+
+/// Every particle other than `i` that `i` learns from in a swarm of `n`, visiting ring neighbors
+/// as i-1, i+1, i-2, i+2, ...
+pub fn neighbors(topology: &Topology, i: usize, n: usize) -> Vec<usize> {
+    match *topology {
+        Topology::Gbest => (0..n).filter(|&j| j != i).collect(),
+        Topology::Ring { k } => (1..=k)
+            .map(|j| {
+                let offset = j.div_ceil(2) % n;
+                if j % 2 == 1 {
+                    (i + n - offset) % n
+                } else {
+                    (i + offset) % n
+                }
+            })
+            .collect(),
+        Topology::VonNeumann => grid_neighbors(i, n).to_vec(),
+    }
+}
+
+/// The particle with the best personal best among `i` and its neighbors, `i` itself on ties
+fn best_neighbor(
+    topology: &Topology,
+    i: usize,
+    n: usize,
+    values: &[f64],
+    opt: &OptimizationPolicy,
+) -> usize {
+    let mut best = i;
+    for neighbor in neighbors(topology, i, n) {
+        let order = opt.compare_indexed((neighbor, values[neighbor]), (best, values[best]));
+        if order == Ordering::Greater {
+            best = neighbor;
+        }
+    }
+    best
+}
+
+/// Rounds the coordinates of `x` listed in `integer` to the nearest integer
+fn round_integers<F: Scalar>(x: &mut [F], integer: &[usize]) {
+    for &j in integer {
+        x[j] = x[j].round();
+    }
+}
+
+/// Sets the coordinates of `x` listed in `fixed` to their values, with no velocity
+fn pin<F: Scalar>(x: &mut [F], v: &mut [F], fixed: &[(usize, f64)]) {
+    for &(j, value) in fixed {
+        x[j] = F::of(value);
+        v[j] = F::zero();
+    }
+}
+
+/// Neighbors of particle `i` above, below, left and right on a grid of `ceil(sqrt(n))` columns;
+/// the last row may be partly empty, and empty cells are skipped over
+fn grid_neighbors(i: usize, n: usize) -> [usize; 4] {
+    let cols = (n as f64).sqrt().ceil() as usize;
+    let rows = n.div_ceil(cols);
+    let step = |dr: usize, dc: usize| {
+        let (mut r, mut c) = (i / cols, i % cols);
+        loop {
+            r = (r + dr) % rows;
+            c = (c + dc) % cols;
+            if r * cols + c < n {
+                return r * cols + c;
+            }
+        }
+    };
+    [step(rows - 1, 0), step(1, 0), step(0, cols - 1), step(0, 1)]
+}
+
+/// Applies the velocity recurrence to one particle with personal best `best` and social
+/// attractor `social`, drawing `r1` and `r2` from `draw`, returning the Euclidean norms of both
+/// attractions
+fn accelerate<F: Scalar>(
+    v: &mut [F],
+    x: &[F],
+    best: &[F],
+    social: &[F],
+    consts: &UpdatePolicy,
+    mut draw: impl FnMut() -> f64,
+) -> Pull {
+    let (w, chi) = match consts.model {
+        VelocityModel::Inertia
+        | VelocityModel::RandomSearch
+        | VelocityModel::BareBones
+        | VelocityModel::Charged
+        | VelocityModel::Gcpso => (consts.w, 1.0),
+        VelocityModel::Constriction | VelocityModel::Fips => (1.0, consts.constriction()),
+    };
+    let (w, chi, c1, c2) = (F::of(w), F::of(chi), F::of(consts.c1), F::of(consts.c2));
+    let mut pull = Pull::default();
+    for (j, vj) in v.iter_mut().enumerate() {
+        let r1 = F::of(draw());
+        let r2 = F::of(draw());
+        let cognitive = if consts.cognitive { c1 * r1 * (best[j] - x[j]) } else { F::zero() };
+        let social = if consts.social { c2 * r2 * (social[j] - x[j]) } else { F::zero() };
+        *vj = chi * (w * *vj + cognitive + social);
+        pull.cognitive += (cognitive * cognitive).widen();
+        pull.social += (social * social).widen();
+    }
+    Pull {
+        cognitive: pull.cognitive.sqrt(),
+        social: pull.social.sqrt(),
+    }
+}
+
+/// Applies the fully informed recurrence, the pull being the mean of `(c1 + c2) * r * (p - x)`
+/// over the personal bests `p` of `informants`, the particle itself first; returns the norms of
+/// the share of the particle's own best and of the share of the others
+fn fully_informed<F: Scalar, R: rand::Rng>(
+    v: &mut [F],
+    x: &[F],
+    informants: &[&[F]],
+    consts: &UpdatePolicy,
+    r: &mut R,
+) -> Pull {
+    let (phi, chi) = (F::of(consts.c1 + consts.c2), F::of(consts.constriction()));
+    let k = F::of(informants.len() as f64);
+    let mut shares = Pull::default();
+    for (j, vj) in v.iter_mut().enumerate() {
+        let on = |term: bool, pull: F| if term { pull } else { F::zero() };
+        let own = on(consts.cognitive, phi * F::of(r.gen::<f64>()) * (informants[0][j] - x[j]));
+        let others = informants[1..]
+            .iter()
+            .map(|p| on(consts.social, phi * F::of(r.gen::<f64>()) * (p[j] - x[j])));
+        let pull = others.fold(own, |pull, other| pull + other);
+        *vj = chi * (*vj + pull / k);
+        shares.cognitive += (own / k).powi(2).widen();
+        shares.social += ((pull - own) / k).powi(2).widen();
+    }
+    Pull {
+        cognitive: shares.cognitive.sqrt(),
+        social: shares.social.sqrt(),
+    }
+}
+
+/// Sets the velocity of GCPSO's best particle to `global - x + w * v + rho * (1 - 2 * r)` per
+/// coordinate, so that its next position is the global best plus a step of at most `w * v + rho`
+fn guaranteed<F: Scalar, R: rand::Rng>(
+    v: &mut [F],
+    x: &[F],
+    global: &[F],
+    w: f64,
+    rho: f64,
+    r: &mut R,
+) {
+    for (j, vj) in v.iter_mut().enumerate() {
+        let step = F::of(rho * (1.0 - 2.0 * r.gen::<f64>()));
+        *vj = global[j] - x[j] + F::of(w) * *vj + step;
+    }
+}
+
+/// Distance below which the repulsion between charged particles stops growing, so that close
+/// encounters give a large but finite kick
+pub const CHARGE_CORE: f64 = 0.01;
+
+/// Adds to the velocity of the charged particle `i` its repulsion from the other charged
+/// particles, those of even index
+fn repel<F: Scalar>(v: &mut [F], i: usize, position: &[Vec<F>], charge: f64) {
+    let x = &position[i];
+    for (k, other) in position.iter().enumerate().step_by(2) {
+        let offset = x.iter().zip(other).map(|(&a, &b)| a - b).collect::<Vec<F>>();
+        let r = offset.iter().map(|&o| o * o).sum::<F>().sqrt();
+        // Coinciding particles have no direction to be pushed in
+        if k == i || r == F::zero() {
+            continue;
+        }
+        let scale = F::of(charge) / r.max(F::of(CHARGE_CORE)).powi(2) / r;
+        for (vj, &o) in v.iter_mut().zip(&offset) {
+            *vj += scale * o;
+        }
+    }
+}
+
+/// Applies the random mutation and the velocity limit after the recurrence
+fn perturb<F: Scalar, R: rand::Rng>(v: &mut [F], consts: &UpdatePolicy, r: &mut R) {
+    // Only draw for the mutation when it is enabled, so that runs without it are unchanged
+    if consts.mutation > 0.0 && r.gen::<f64>() < consts.mutation {
+        for vj in v.iter_mut() {
+            *vj += F::of(consts.mutation_scale * gaussian(r));
+        }
+    }
+    if let Some(vmax) = consts.vmax {
+        let vmax = F::of(vmax);
+        for vj in v.iter_mut() {
+            *vj = vj.clamp(-vmax, vmax);
+        }
+    }
+}
+
+/// A standard normal sample by the Box-Muller transform
+fn gaussian<R: rand::Rng>(r: &mut R) -> f64 {
+    let u1 = 1.0 - r.gen::<f64>();
+    let u2 = r.gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+/// Diagonal of the smallest box holding every position, which bounds the largest distance
+/// between two particles from above, and from below within a factor of `sqrt(d)`
+pub fn diameter<F: Scalar>(position: &[Vec<F>]) -> f64 {
+    let d = position.first().map_or(0, Vec::len);
+    let extent = |j: usize| {
+        let lower = position.iter().map(|x| x[j]).fold(F::infinity(), F::min);
+        let upper = position.iter().map(|x| x[j]).fold(F::neg_infinity(), F::max);
+        (upper - lower).widen()
+    };
+    (0..d).map(|j| extent(j).powi(2)).sum::<f64>().sqrt()
+}
+
+/// Mean absolute velocity over every particle and coordinate
+pub fn mean_velocity<F: Scalar>(swarm: &ParticleSwarm<F>) -> f64 {
+    let speeds = swarm.velocity.iter().flatten().map(|v| v.abs().widen());
+    let count = swarm.velocity.iter().map(|v| v.len()).sum::<usize>();
+    speeds.sum::<f64>() / count as f64
+}
+
+const CSV_HEADER: &str = "iteration,global_best_x,global_best_value,mean_velocity,best_particle_index";
+
+/// Logs `swarm` at the trace level, in full if `dump`
+fn trace_swarm<F: Scalar>(swarm: &ParticleSwarm<F>, dump: bool, digits: Option<usize>) {
+    match (dump, digits) {
+        (true, Some(p)) => trace!("{:#.*}", p, swarm),
+        (true, None) => trace!("{:#}", swarm),
+        (false, Some(p)) => trace!("{:.*}", p, swarm),
+        (false, None) => trace!("{}", swarm),
+    }
+}
+
+/// Appends the state after iteration `i` to the CSV trajectory, coordinates separated by `;`
+fn write_csv_row<W: Write, F: Scalar>(
+    csv: &mut Option<W>,
+    i: usize,
+    swarm: &ParticleSwarm<F>,
+) -> io::Result<()> {
+    let Some(w) = csv else {
+        return Ok(());
+    };
+    let best = swarm.global_optimum.as_ref().unwrap();
+    let x = best.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+    writeln!(
+        w,
+        "{},{},{},{},{}",
+        i,
+        x.join(";"),
+        swarm.global_value(),
+        mean_velocity(swarm),
+        swarm.best_index
+    )
+}
+
+/// Statistics of a swarm, see [`ParticleSwarm::summary`]
+#[derive(Debug, Clone, Serialize)]
+pub struct SwarmSummary {
+    /// The global best and its objective value
+    pub best_position: Vec<f64>,
+    pub best_value: f64,
+    pub best_index: usize,
+    /// Smallest, mean and largest position of the particles in each coordinate
+    pub min: Vec<f64>,
+    pub mean: Vec<f64>,
+    pub max: Vec<f64>,
+    /// Absolute velocity in each coordinate averaged over the particles
+    pub mean_speed: Vec<f64>,
+    /// Objective value averaged over the current positions
+    pub mean_objective: f64,
+    /// Euclidean norm of the velocities averaged over the particles
+    pub velocity_norm: f64,
+    /// See [`diameter`]
+    pub diameter: f64,
+}
+
+/// Mean of the last values pushed, at most `window` of them, kept in a ring buffer
+#[derive(Debug, Clone)]
+struct MovingAverage {
+    values: Vec<f64>,
+    window: usize,
+    /// Slot of the oldest value once the buffer is full, which the next value replaces
+    next: usize,
+}
+
+impl MovingAverage {
+    /// A window of `window` values, already holding the last of `values`, oldest first
+    fn new(window: usize, values: &[f64]) -> Self {
+        let mut average = Self {
+            values: Vec::with_capacity(window),
+            window,
+            next: 0,
+        };
+        for &value in values {
+            average.push(value);
+        }
+        average
+    }
+
+    fn push(&mut self, value: f64) {
+        if self.values.len() < self.window {
+            self.values.push(value);
+        } else {
+            self.values[self.next] = value;
+            self.next = (self.next + 1) % self.window;
+        }
+    }
+
+    /// NaN before the first value
+    fn mean(&self) -> f64 {
+        self.values.iter().sum::<f64>() / self.values.len() as f64
+    }
+
+    /// The values in the window, oldest first
+    fn values(&self) -> Vec<f64> {
+        [&self.values[self.next..], &self.values[..self.next]].concat()
+    }
+}
+
+/// Summary of the swarm after one iteration
+#[derive(Serialize)]
+pub struct IterationStats {
+    /// Objective value of the global best
+    pub global_best: f64,
+    /// Objective value averaged over the current positions
+    pub mean_objective: f64,
+    pub best_index: usize,
+    /// Euclidean norm of the velocities averaged over the particles
+    pub velocity_norm: f64,
+    /// See [`diameter`]
+    pub diameter: f64,
+    /// Global best averaged over the last [`PsoConfig::ma_window`] iterations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moving_average: Option<f64>,
+}
+
+impl IterationStats {
+    fn of<F: Scalar>(swarm: &ParticleSwarm<F>, average: Option<&MovingAverage>) -> Self {
+        let summary = swarm.summary();
+        Self {
+            global_best: summary.best_value,
+            mean_objective: summary.mean_objective,
+            best_index: summary.best_index,
+            velocity_norm: summary.velocity_norm,
+            diameter: summary.diameter,
+            moving_average: average.map(MovingAverage::mean),
+        }
+    }
+}
+
+/// One line of the event log written to stderr with [`PsoConfig::events`]
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// The swarm is initialized or resumed, after `i` iterations
+    Init {
+        n: usize,
+        d: usize,
+        seed: u64,
+        i: usize,
+        best: f64,
+    },
+    /// Iteration `i` is done; `best` is the objective value of the global best
+    Iteration {
+        i: usize,
+        best: f64,
+        mean_objective: f64,
+        velocity_norm: f64,
+    },
+    Done {
+        iterations: usize,
+        best: f64,
+        stop_reason: StopReason,
+    },
+}
+
+impl Event {
+    /// Writes the event as a single JSON line, so that the log can be read while the run goes on
+    fn emit(&self) {
+        eprintln!("{}", serde_json::to_string(self).unwrap());
+    }
+}
+
+/// Fraction of the particles that must lie within the convergence radius of the best for the
+/// swarm to count as converged
+pub const CONVERGED_FRACTION: f64 = 0.5;
+
+/// How far the final swarm is spread around the best position
+#[derive(Serialize)]
+pub struct Spread {
+    /// Standard deviation of the particle positions in each coordinate
+    pub std_dev: Vec<f64>,
+    pub radius: f64,
+    /// Fraction of the particles within Euclidean distance `radius` of the best position
+    pub within_radius: f64,
+}
+
+impl Spread {
+    pub fn of<F: Scalar>(position: &[Vec<F>], best: &[F], radius: f64) -> Self {
+        let n = position.len() as f64;
+        let std_dev = (0..best.len())
+            .map(|j| {
+                let mean = position.iter().map(|x| x[j].widen()).sum::<f64>() / n;
+                let variance =
+                    position.iter().map(|x| (x[j].widen() - mean).powi(2)).sum::<f64>() / n;
+                variance.sqrt()
+            })
+            .collect();
+        let distance = |x: &Vec<F>| {
+            x.iter()
+                .zip(best)
+                .map(|(a, b)| (a.widen() - b.widen()).powi(2))
+                .sum::<f64>()
+                .sqrt()
+        };
+        let within = position.iter().filter(|x| distance(x) <= radius).count();
+        Self {
+            std_dev,
+            radius,
+            within_radius: within as f64 / n,
+        }
+    }
+
+    /// Whether too few particles gathered around the best, see [`CONVERGED_FRACTION`]
+    pub fn diverse(&self) -> bool {
+        self.within_radius < CONVERGED_FRACTION
+    }
+}
+
+/// Per-iteration diagnostics of a run
+#[derive(Serialize, Default)]
+pub struct History {
+    pub iterations: Vec<IterationStats>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// The fixed number of iterations was reached
+    Iterations,
+    /// The best value came within the tolerance of the target
+    Target,
+    /// The global best stopped improving
+    Stagnation,
+    /// The mean velocity fell below the velocity tolerance
+    VelocityTolerance,
+    /// [`PsoConfig::on_iteration`] asked to stop
+    Callback,
+    /// The time limit ran out
+    TimeLimit,
+    /// [`PsoConfig::interrupt`] was set
+    Interrupted,
+    /// Another iteration would have gone past [`StoppingRule::evaluations`]
+    EvaluationBudget,
+}
+
+#[derive(Serialize)]
+pub struct RunResult {
+    pub iterations: usize,
+    pub best_position: Vec<f64>,
+    pub best_value: f64,
+    pub seed: u64,
+    /// Whether the run stopped on its own rather than by exhausting the iterations or the time
+    /// limit, or by an interrupt
+    pub converged: bool,
+    pub stop_reason: StopReason,
+    /// Wall-clock time of the iterations in seconds, excluding the initialization of the swarm
+    pub elapsed_secs: f64,
+    pub iterations_per_sec: f64,
+    /// Number of evaluations at particle positions that returned NaN
+    pub nan_evaluations: usize,
+    /// Evaluations of the objective by the run, the polish and the run before a warm start
+    /// included but not earlier restarts
+    pub evaluations: usize,
+    /// Spread of the final swarm around `best_position`
+    pub spread: Spread,
+    /// Positions of the particles when the run stopped
+    #[serde(skip)]
+    pub final_position: Vec<Vec<f64>>,
+    /// First iteration after which the swarm had collapsed below [`PsoConfig::diameter_eps`]
+    /// short of the target, a sign of premature convergence
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collapsed_at: Option<usize>,
+    /// Whether `best_position` satisfies every constraint, only with a [`Penalty`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feasible: Option<bool>,
+    /// Diagnostics of every iteration, only recorded with `record_history`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<History>,
+    /// `best_value` as the swarm left it, only with [`PsoConfig::polish`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_polish_value: Option<f64>,
+    /// Euclidean distance from `best_position` to the known optimum, see
+    /// [`RunResult::compare_to_optimum`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position_error: Option<f64>,
+    /// Absolute difference between `best_value` and the value at the known optimum
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_gap: Option<f64>,
+    /// Value of every objective at `best_position` when `best_value` is their weighted sum, filled
+    /// in by the caller
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub objective_values: Option<Vec<f64>>,
+}
+
+impl RunResult {
+    /// Measures the result against the optimum `x` of value `y`, filling in `position_error` and
+    /// `value_gap`
+    pub fn compare_to_optimum(&mut self, x: &[f64], y: f64) {
+        let squared = self.best_position.iter().zip(x).map(|(a, b)| (a - b).powi(2));
+        self.position_error = Some(squared.sum::<f64>().sqrt());
+        self.value_gap = Some((self.best_value - y).abs());
+    }
+}
+
+/// Inequality constraints `g(x) <= 0`, each violated one adding `coeff * g(x)^2` to the
+/// objective, so that particles may cross into the infeasible region but pay for staying there
+pub struct Penalty {
+    /// The functions `g`, positive where their constraint is violated
+    pub constraints: Vec<ObjectiveFn>,
+    pub coeff: f64,
+}
+
+impl Penalty {
+    /// The penalty at `x`, zero where every constraint holds
+    pub fn at(&self, x: &[f64]) -> f64 {
+        let violations = self.constraints.iter().map(|g| g(x).max(0.0).powi(2));
+        self.coeff * violations.sum::<f64>()
+    }
+
+    pub fn feasible(&self, x: &[f64]) -> bool {
+        self.constraints.iter().all(|g| g(x) <= 0.0)
+    }
+}
+
+/// When a run ends; with several of an iteration limit, a target, a time limit and an evaluation
+/// budget, whichever comes first
+#[derive(Debug, Clone, Copy)]
+pub struct StoppingRule {
+    /// Run at most this many iterations
+    pub iterations: Option<usize>,
+    pub target: Option<Target>,
+    /// Wall-clock budget of every run, checked every [`TIME_CHECK_INTERVAL`] iterations
+    pub time: Option<Duration>,
+    /// Objective evaluations of every run, initialization included; the run stops before an
+    /// iteration that would take as many evaluations as the last one and go past the budget
+    pub evaluations: Option<usize>,
+}
+
+/// Iterations between two reads of the clock against [`StoppingRule::time`], so that the time
+/// limit does not slow down cheap objectives; the budget may be overrun by this many iterations
+pub const TIME_CHECK_INTERVAL: usize = 10;
+
+/// Run until the best value is within `tol` of `target`, giving up once it has improved by no more
+/// than `stag_eps` for `stag_window` consecutive iterations
+#[derive(Debug, Clone, Copy)]
+pub struct Target {
+    pub target: f64,
+    pub tol: f64,
+    pub stag_eps: f64,
+    pub stag_window: usize,
+    /// Measure the improvements on the moving average of [`PsoConfig::ma_window`] rather than on
+    /// the best value itself, so that a noisy best does not pass for progress or its absence
+    pub smoothed: bool,
+}
+
+pub struct PsoConfig {
+    /// Number of particles
+    pub n: usize,
+    /// Number of coordinates of each particle
+    pub d: usize,
+    pub policy: UpdatePolicy,
+    /// Schedule replacing the inertia weight of `policy`, only followed with a fixed number of
+    /// iterations
+    pub w_schedule: Option<LinearSchedule>,
+    /// Schedule replacing `c1` and `c2` of `policy`, only followed with a fixed number of
+    /// iterations
+    pub acc_schedule: Option<AccelerationSchedule>,
+    pub opt: OptimizationPolicy,
+    pub stop: StoppingRule,
+    /// Additionally stop once the mean absolute velocity falls below this
+    pub vel_tol: Option<f64>,
+    /// Average the global best over this many last iterations in [`IterationStats`]
+    pub ma_window: Option<usize>,
+    /// Report in [`RunResult::collapsed_at`] when the [`diameter`] of the swarm falls below this
+    /// before the target is reached
+    pub diameter_eps: Option<f64>,
+    /// Scatter the swarm with [`ParticleSwarm::scatter`] whenever it collapses below
+    /// `diameter_eps`
+    pub diameter_reinit: bool,
+    /// Distance from the best position within which a particle counts as converged
+    pub conv_radius: f64,
+    /// Split the coordinates among this many sub-swarms of `n` particles each, see [`cooperative`]
+    pub cooperative: Option<usize>,
+    /// Weight of every particle in the choice of the global best, see
+    /// [`OptimizationPolicy::weigh`]; equal weights when absent
+    pub weights: Option<Vec<f64>>,
+    pub bounds: Option<Bounds>,
+    /// Coordinates restricted to integers, by rounding every position
+    pub integer_dims: Vec<usize>,
+    /// Coordinates held at a value, each as its index and the value, from the initial positions on
+    pub fixed: Vec<(usize, f64)>,
+    pub objective: ObjectiveFn,
+    /// Constraints added to the objective wherever it is evaluated, so `best_value` includes the
+    /// penalty of `best_position`
+    pub penalty: Option<Penalty>,
+    /// Standard deviation of Gaussian noise added to every evaluation of the objective, drawn from
+    /// the seed of the run; with the rayon feature the draws go to the evaluations in any order
+    pub noise: Option<f64>,
+    /// Evaluate the personal bests again every this many iterations, so that a noisy objective
+    /// does not hold on to a lucky draw, see [`ParticleSwarm::reevaluate_bests`]
+    pub reeval_every: Option<usize>,
+    /// Refine the best position after the run with this many rounds of
+    /// [`polish::compass_search`] on the penalized objective
+    pub polish: Option<usize>,
+    /// The program behind `objective` when it is external, whose failure ends the run
+    pub program: Option<Arc<external::Program>>,
+    /// Range of random initial positions, the bounds when absent or `[-1, 1]` without bounds
+    pub init_range: Option<(f64, f64)>,
+    /// How random initial positions are spread over their range
+    pub init_method: InitMethod,
+    /// Start this many of the random particles, at most `n`, at the corners of the range of
+    /// initial positions, see [`init::corners`]
+    pub seed_corners: usize,
+    /// Opposition-based initialization: replace every random position `x` by its opposite
+    /// `lower + upper - x` across the range of initial positions where that is better
+    pub obl_init: bool,
+    /// Evaluate a [`init::grid`] of this many cells along each coordinate of the range of initial
+    /// positions, and start the random particles at uniform positions within the best cells, as
+    /// many as there are
+    pub grid_seed: Option<usize>,
+    /// Range of random initial velocities, `[-1, 1]` when absent
+    pub vinit_range: Option<(f64, f64)>,
+    /// Initial positions, drawn at random when absent
+    pub init: Option<Vec<Vec<f64>>>,
+    /// Initial velocities used with `init`, zero when absent
+    pub vinit: Option<Vec<Vec<f64>>>,
+    pub precision: Precision,
+    pub seed: u64,
+    /// Seeds of the first runs in order, the repeats of [`run_repeats`] or else the restarts,
+    /// starting with `seed`; runs past the end of the list are seeded from `seed` as without it
+    pub seeds: Vec<u64>,
+    /// Number of independent runs, each seeded by [`restart_seed`], of which the best is kept
+    pub restarts: usize,
+    /// Draw every run from one random stream started from `seed`, continuing from where the
+    /// previous restart, objective or repeat left it, instead of from a seed per run; the runs can
+    /// then only be reproduced together, and the seed of each result merely labels it
+    pub resume_rng: bool,
+    /// Destination of the per-iteration trajectory in CSV format
+    pub csv: Option<Box<dyn Write>>,
+    /// Collect [`IterationStats`] after every iteration into [`RunResult::history`]
+    pub record_history: bool,
+    /// Show a progress bar on stderr, only with a fixed number of iterations and without the event
+    /// log
+    pub progress: bool,
+    /// Log every position and velocity of the swarm at the trace level, rather than a summary per
+    /// coordinate
+    pub dump_swarm: bool,
+    /// Digits after the decimal point of the numbers in the logged swarm, all of them by default
+    pub digits: Option<usize>,
+    /// Write an [`Event`] to stderr once the swarm is initialized, after every iteration and at the
+    /// end of the run
+    pub events: bool,
+    /// Called with the state after every iteration; returning `ControlFlow::Break` ends the run
+    pub on_iteration: Option<IterationCallback>,
+    /// File that a [`Checkpoint`] is written to every `checkpoint_every` iterations, replacing the
+    /// previous one
+    pub checkpoint: Option<PathBuf>,
+    pub checkpoint_every: usize,
+    /// State to continue the first run from, instead of initializing a new swarm
+    pub warm_start: Option<Checkpoint>,
+    /// Flag that ends the run before the next iteration once set, e.g. from a signal handler, with
+    /// the best found so far; no further restarts, repeats or objectives are run after it
+    pub interrupt: Option<Arc<AtomicBool>>,
+}
+
+impl PsoConfig {
+    /// Whether [`PsoConfig::interrupt`] was set
+    pub fn interrupted(&self) -> bool {
+        self.interrupted_flag()()
+    }
+
+    /// [`PsoConfig::interrupted`] without holding on to the rest of the configuration
+    fn interrupted_flag(&self) -> impl Fn() -> bool {
+        let flag = self.interrupt.clone();
+        move || flag.as_ref().is_some_and(|flag| flag.load(atomic::Ordering::Relaxed))
+    }
+
+    /// [`PsoConfig::policy`] in iteration `k`, with the scheduled coefficients
+    fn policy_at(&self, k: usize) -> UpdatePolicy {
+        self.schedule()(k)
+    }
+
+    /// [`PsoConfig::policy_at`] without holding on to the rest of the configuration
+    fn schedule(&self) -> impl Fn(usize) -> UpdatePolicy {
+        let (policy, iterations) = (self.policy, self.stop.iterations);
+        let (w_schedule, acc_schedule) = (self.w_schedule, self.acc_schedule);
+        move |k| {
+            let mut consts = policy;
+            // Schedules run over the iteration limit, so without one the coefficients stay
+            // constant
+            if let Some(i) = iterations {
+                if let Some(schedule) = w_schedule {
+                    consts.w = schedule.at(k, i);
+                }
+                if let Some(schedule) = acc_schedule {
+                    consts.c1 = schedule.c1.at(k, i);
+                    consts.c2 = schedule.c2.at(k, i);
+                }
+            }
+            consts
+        }
+    }
+}
+
+/// Everything needed to continue a run where it stopped, so that the rest of the run is the same
+/// as without the interruption; the swarm is saved in `f64` whatever the precision of the run
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Number of iterations done
+    pub iteration: usize,
+    pub swarm: ParticleSwarm,
+    /// Iterations without improvement so far, and the value improvements are measured against
+    pub stagnant: usize,
+    pub stagnation_value: f64,
+    /// Global best of the last iterations in the moving average window, oldest first
+    #[serde(default)]
+    pub recent: Vec<f64>,
+    /// Evaluations of the objective so far, which the resumed run goes on counting from
+    #[serde(default)]
+    pub evaluations: usize,
+}
+
+impl Checkpoint {
+    /// Reads a checkpoint written by [`Checkpoint::write`]; the objective values of the swarm
+    /// are left empty until [`ParticleSwarm::evaluate`]
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Writes the checkpoint as JSON next to `path` first, so that an interrupted write leaves
+    /// the previous checkpoint intact
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let partial = path.with_extension("partial");
+        let mut w = BufWriter::new(File::create(&partial)?);
+        serde_json::to_writer(&mut w, self)?;
+        w.flush()?;
+        fs::rename(partial, path)
+    }
+}
+
+#[derive(Debug)]
+pub enum RunError {
+    /// The configuration describes no swarm that can be built, e.g. `init` has the wrong shape
+    InvalidConfig(String),
+    /// The objective is NaN at every initial position, so no particle can be ranked
+    AllNaN,
+    /// Writing the CSV trajectory or a checkpoint failed
+    Io(io::Error),
+    /// The external objective program could not be run or printed no number
+    ExternalObjective(String),
+    /// Every particle has an infinite or NaN coordinate, e.g. from an inertia weight above one
+    /// without bounds
+    Diverged,
+    /// With a [`Penalty`], no position evaluated during the run satisfied every constraint
+    InfeasibleSwarm,
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunError::InvalidConfig(reason) => write!(f, "Invalid configuration: {}", reason),
+            RunError::AllNaN => write!(f, "The objective is NaN at every initial position"),
+            RunError::Io(e) => write!(f, "Failed to write output: {}", e),
+            RunError::ExternalObjective(reason) => write!(f, "The objective program failed: {}", reason),
+            RunError::Diverged => {
+                write!(f, "The swarm diverged: every particle left the finite numbers")
+            }
+            RunError::InfeasibleSwarm => {
+                write!(f, "No particle ever satisfied every constraint; try a larger penalty")
+            }
+        }
+    }
+}
+
+impl Error for RunError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RunError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RunError {
+    fn from(e: io::Error) -> Self {
+        RunError::Io(e)
+    }
+}
+
+/// Checks what [`ParticleSwarm::new`] would otherwise assert
+fn validate(config: &PsoConfig) -> Result<(), RunError> {
+    let (n, d) = (config.n, config.d);
+    if n == 0 {
+        return Err(RunError::InvalidConfig("the swarm needs at least one particle".to_string()));
+    }
+    let stop = &config.stop;
+    let unbounded = stop.iterations.is_none() && stop.target.is_none() && stop.time.is_none();
+    if unbounded && stop.evaluations.is_none() {
+        let reason =
+            "the run needs an iteration limit, a target, a time limit or an evaluation budget";
+        return Err(RunError::InvalidConfig(reason.to_string()));
+    }
+    // Every sub-swarm evaluates its particles once to begin with, after the cells of a grid seed,
+    // and the opposites of those that neither the grid nor the corners place
+    let grid_cells = config.grid_seed.map_or(0, |points| points.saturating_pow(d as u32));
+    let replaced = grid_cells.max(config.seed_corners).min(n);
+    let opposites = if config.obl_init { n - replaced } else { 0 };
+    let initial = (n * config.cooperative.unwrap_or(1) + opposites).saturating_add(grid_cells);
+    if stop.evaluations.is_some_and(|budget| budget < initial) {
+        let reason = "the evaluation budget does not cover the initialization of the swarm";
+        return Err(RunError::InvalidConfig(reason.to_string()));
+    }
+    if let Some(j) = config.integer_dims.iter().find(|&&j| j >= d) {
+        return Err(RunError::InvalidConfig(format!("integer coordinate {} is out of range", j)));
+    }
+    if let Some((j, _)) = config.fixed.iter().find(|&&(j, _)| j >= d) {
+        return Err(RunError::InvalidConfig(format!("fixed coordinate {} is out of range", j)));
+    }
+    if config.reeval_every == Some(0) {
+        return Err(RunError::InvalidConfig("re-evaluations need a positive interval".to_string()));
+    }
+    if config.weights.as_ref().is_some_and(|w| w.len() != n) {
+        return Err(RunError::InvalidConfig(format!("weights must be {} values, one per particle", n)));
+    }
+    if config.weights.iter().flatten().any(|w| !w.is_finite() || *w <= 0.0) {
+        return Err(RunError::InvalidConfig("weights must be finite and positive".to_string()));
+    }
+    if config.polish == Some(0) {
+        return Err(RunError::InvalidConfig("the polish needs at least one round".to_string()));
+    }
+    if config.checkpoint.is_some() && config.checkpoint_every == 0 {
+        return Err(RunError::InvalidConfig("checkpoints need a positive interval".to_string()));
+    }
+    if config.warm_start.is_some() && config.restarts > 1 {
+        return Err(RunError::InvalidConfig("a warm start continues a single run".to_string()));
+    }
+    if let Some(c) = &config.warm_start {
+        if c.swarm.position.len() != n
+            || c.swarm.position.iter().any(|x| x.len() != d)
+            || c.swarm.stale.len() != n
+        {
+            let reason = format!("the checkpoint is not a swarm of {} particles of {} coordinates", n, d);
+            return Err(RunError::InvalidConfig(reason));
+        }
+    }
+    let fits = |rows: &Vec<Vec<f64>>| rows.len() == n && rows.iter().all(|row| row.len() == d);
+    if let Some(x) = &config.init {
+        if !fits(x) {
+            return Err(RunError::InvalidConfig(format!("init must be {} rows of {} coordinates", n, d)));
+        }
+        if !config.vinit.as_ref().is_none_or(fits) {
+            return Err(RunError::InvalidConfig(format!("vinit must be {} rows of {} coordinates", n, d)));
+        }
+    }
+    if let Some(b) = &config.bounds {
+        if b.lower.len() != d || b.upper.len() != d {
+            return Err(RunError::InvalidConfig(format!("bounds must have {} intervals", d)));
+        }
+    }
+    if config.ma_window == Some(0) {
+        return Err(RunError::InvalidConfig("the moving average needs a window".to_string()));
+    }
+    if config.stop.target.is_some_and(|t| t.smoothed) && config.ma_window.is_none() {
+        let reason = "a smoothed stagnation check needs a moving average window";
+        return Err(RunError::InvalidConfig(reason.to_string()));
+    }
+    if let Some(points) = config.grid_seed {
+        if points == 0 {
+            let reason = "the grid needs at least one cell along each coordinate";
+            return Err(RunError::InvalidConfig(reason.to_string()));
+        }
+        let cells = points.checked_pow(d as u32).filter(|&cells| cells <= init::MAX_GRID_CELLS);
+        if cells.is_none() {
+            let reason = format!(
+                "a grid of {} cells along {} coordinates has more than {} cells",
+                points,
+                d,
+                init::MAX_GRID_CELLS
+            );
+            return Err(RunError::InvalidConfig(reason));
+        }
+    }
+    if let Some(s) = config.cooperative {
+        if s == 0 || s > d {
+            let reason = format!("{} coordinates cannot be split among {} sub-swarms", d, s);
+            return Err(RunError::InvalidConfig(reason));
+        }
+        // These follow a single swarm
+        let unsupported = [
+            (config.warm_start.is_some(), "a warm start"),
+            (config.checkpoint.is_some(), "checkpoints"),
+            (config.init.is_some(), "initial positions"),
+            (config.seed_corners > 0, "corner particles"),
+            (config.policy.elitism, "elitism"),
+            (config.obl_init, "opposition-based initialization"),
+            (config.grid_seed.is_some(), "a grid of initial positions"),
+            (config.csv.is_some(), "a CSV trajectory"),
+            (config.record_history, "a history"),
+            (config.on_iteration.is_some(), "an iteration callback"),
+            (config.vel_tol.is_some(), "a velocity tolerance"),
+            (config.diameter_eps.is_some(), "a diameter threshold"),
+            (config.ma_window.is_some(), "a moving average"),
+        ];
+        if let Some((_, what)) = unsupported.iter().find(|(used, _)| *used) {
+            let reason = format!("cooperative sub-swarms do not support {}", what);
+            return Err(RunError::InvalidConfig(reason));
+        }
+    }
+    Ok(())
+}
+
+/// Seed of the `restart`-th run: the base seed itself for the first run, and a SplitMix64 step
+/// away from it for every other run
+pub fn restart_seed(base: u64, restart: usize) -> u64 {
+    if restart == 0 {
+        return base;
+    }
+    let mut z = base.wrapping_add((restart as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Runs the optimizer to completion, keeping the best of all restarts
+pub fn run(config: PsoConfig) -> Result<RunResult, RunError> {
+    Ok(run_each(config, Vec::new())?.remove(0))
+}
+
+/// Runs `config`, then runs it again from the same seed with each of `others` in place of its
+/// objective, returning one result per objective; CSV rows of the runs follow each other
+pub fn run_each(mut config: PsoConfig, others: Vec<ObjectiveFn>) -> Result<Vec<RunResult>, RunError> {
+    validate(&config)?;
+    if let Some(w) = &mut config.csv {
+        writeln!(w, "{}", CSV_HEADER)?;
+    }
+
+    let mut stream = config.resume_rng.then(|| StdRng::seed_from_u64(config.seed));
+    let mut results = vec![run_restarts(&mut config, &mut stream)?];
+    for objective in others {
+        if config.interrupted() {
+            break;
+        }
+        config.objective = objective;
+        results.push(run_restarts(&mut config, &mut stream)?);
+    }
+
+    if let Some(w) = &mut config.csv {
+        w.flush()?;
+    }
+    Ok(results)
+}
+
+/// Runs `config` with each of `policies` in place of its own, every one from the same seed,
+/// returning one result per policy; CSV rows of the runs follow each other
+pub fn run_policies(
+    mut config: PsoConfig,
+    policies: &[UpdatePolicy],
+) -> Result<Vec<RunResult>, RunError> {
+    validate(&config)?;
+    if let Some(w) = &mut config.csv {
+        writeln!(w, "{}", CSV_HEADER)?;
+    }
+
+    let mut stream = config.resume_rng.then(|| StdRng::seed_from_u64(config.seed));
+    let mut results = Vec::with_capacity(policies.len());
+    for policy in policies {
+        if config.interrupted() {
+            break;
+        }
+        config.policy = *policy;
+        results.push(run_restarts(&mut config, &mut stream)?);
+    }
+
+    if let Some(w) = &mut config.csv {
+        w.flush()?;
+    }
+    Ok(results)
+}
+
+/// Runs `config` once from each of the seeds `seed`, `seed + 1`, ..., `seed + repeats - 1`, returning
+/// every result rather than only the best
+pub fn run_repeats(mut config: PsoConfig, repeats: usize) -> Result<Vec<RunResult>, RunError> {
+    validate(&config)?;
+    if let Some(w) = &mut config.csv {
+        writeln!(w, "{}", CSV_HEADER)?;
+    }
+
+    // The listed seeds belong to the repeats, whose restarts are derived from them
+    let (base, seeds) = (config.seed, std::mem::take(&mut config.seeds));
+    let mut stream = config.resume_rng.then(|| StdRng::seed_from_u64(base));
+    let mut results = Vec::with_capacity(repeats);
+    for i in 0..repeats {
+        if config.interrupted() {
+            break;
+        }
+        config.seed = seeds.get(i).copied().unwrap_or(base.wrapping_add(i as u64));
+        results.push(run_restarts(&mut config, &mut stream)?);
+    }
+
+    if let Some(w) = &mut config.csv {
+        w.flush()?;
+    }
+    Ok(results)
+}
+
+/// Statistics of the best values of independent runs
+#[derive(Serialize)]
+pub struct Summary {
+    pub min: f64,
+    pub median: f64,
+    pub mean: f64,
+    pub max: f64,
+    /// Sample standard deviation, zero for a single run
+    pub std_dev: f64,
+    /// Fraction of the runs whose best value came within `tol` of `target`
+    pub success_rate: f64,
+}
+
+impl Summary {
+    pub fn of(results: &[RunResult], target: f64, tol: f64) -> Self {
+        let mut values = results.iter().map(|r| r.best_value).collect::<Vec<f64>>();
+        values.sort_by(f64::total_cmp);
+        let k = values.len();
+        let mean = values.iter().sum::<f64>() / k as f64;
+        let median = if k % 2 == 1 {
+            values[k / 2]
+        } else {
+            (values[k / 2 - 1] + values[k / 2]) / 2.0
+        };
+        let variance = if k > 1 {
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (k - 1) as f64
+        } else {
+            0.0
+        };
+        let successes = values.iter().filter(|v| (*v - target).abs() <= tol).count();
+        Self {
+            min: values[0],
+            median,
+            mean,
+            max: values[k - 1],
+            std_dev: variance.sqrt(),
+            success_rate: successes as f64 / k as f64,
+        }
+    }
+}
+
+/// Runs every restart of `config`, keeping the best
+fn run_restarts(config: &mut PsoConfig, stream: &mut Option<StdRng>) -> Result<RunResult, RunError> {
+    let mut best: Option<RunResult> = None;
+    for restart in 0..config.restarts.max(1) {
+        let seed = match config.seeds.get(restart) {
+            Some(&seed) => seed,
+            None => restart_seed(config.seed, restart),
+        };
+        let result = match config.precision {
+            Precision::F64 => run_once::<f64>(config, seed, stream.as_mut())?,
+            Precision::F32 => run_once::<f32>(config, seed, stream.as_mut())?,
+        };
+        if config.restarts > 1 {
+            debug!(restart = restart + 1, best_value = result.best_value, "restart finished");
+        }
+        let improves = match &best {
+            None => true,
+            Some(b) => config.opt.better(result.best_value, b.best_value),
+        };
+        if improves {
+            best = Some(result);
+        }
+        if config.interrupted() {
+            break;
+        }
+    }
+    Ok(best.unwrap())
+}
+
+/// A new swarm for `config`, from its initial positions or drawn from `r`, with the particles'
+/// own streams seeded from `stream_seed`
+fn initial_swarm<F: Scalar>(
+    config: &PsoConfig,
+    f: &(dyn Fn(&[f64]) -> f64 + Sync),
+    r: &mut StdRng,
+    stream_seed: u64,
+) -> ParticleSwarm<F> {
+    let (n, d, opt) = (config.n, config.d, &config.opt);
+    let bounds = config.bounds.as_ref();
+    // Rounded and pinned before they are evaluated, so that the first personal bests are too
+    let settle = |xi: &mut Vec<F>, vi: &mut Vec<F>| {
+        round_integers(xi, &config.integer_dims);
+        pin(xi, vi, &config.fixed);
+    };
+    let (mut x, mut v, value) = match &config.init {
+        Some(x) => {
+            let x = x.iter().map(|xi| convert(xi)).collect::<Vec<Vec<F>>>();
+            let v = match &config.vinit {
+                Some(v) => v.iter().map(|vi| convert(vi)).collect(),
+                None => vec![vec![F::zero(); d]; n],
+            };
+            (x, v, vec![None; n])
+        }
+        None => {
+            let x_ranges = match (config.init_range, bounds) {
+                (Some(range), _) => vec![range; d],
+                (None, Some(b)) => (0..d).map(|j| b.range(j)).collect(),
+                (None, None) => vec![(-1.0, 1.0); d],
+            };
+            let v_range = config.vinit_range.unwrap_or((-1.0, 1.0));
+            let (mut x, mut v) = match config.init_method {
+                InitMethod::Uniform => random_start(n, &x_ranges, v_range, r),
+                InitMethod::Halton | InitMethod::LatinHypercube => {
+                    let x = match config.init_method {
+                        InitMethod::Halton => init::halton(n, &x_ranges, r),
+                        _ => init::latin_hypercube(n, &x_ranges, r),
+                    };
+                    let x = x.iter().map(|xi| convert(xi)).collect();
+                    let (lower, upper) = (F::of(v_range.0), F::of(v_range.1));
+                    let mut draw = || rand::Rng::gen_range(r, lower..=upper);
+                    (x, (0..n).map(|_| (0..d).map(|_| draw()).collect()).collect())
+                }
+            };
+            x.iter_mut().zip(v.iter_mut()).for_each(|(xi, vi)| settle(xi, vi));
+            // The first particles move to the grid and the corners, and only the others are opposed
+            let grid_cells = config.grid_seed.map_or(0, |points| points.pow(d as u32));
+            let replaced = grid_cells.max(config.seed_corners).min(n);
+            let mut value = vec![None; n];
+            if config.obl_init {
+                let particles = x.iter_mut().zip(v.iter_mut()).zip(value.iter_mut());
+                for ((xi, vi), yi) in particles.skip(replaced) {
+                    let opposite = xi.iter().zip(&x_ranges);
+                    let mut opposite = opposite
+                        .map(|(&xj, &(lower, upper))| F::of(lower + upper) - xj)
+                        .collect::<Vec<F>>();
+                    settle(&mut opposite, vi);
+                    let (y, z) = (F::eval(f, xi), F::eval(f, &opposite));
+                    if opt.better(z, y) {
+                        (*xi, *yi) = (opposite, Some(z));
+                    } else {
+                        *yi = Some(y);
+                    }
+                }
+            }
+            if let Some(points) = config.grid_seed {
+                let grid = init::grid(points, &x_ranges).into_iter();
+                let mut cells = grid.map(|c| (f(&c), c)).collect::<Vec<_>>();
+                // The better value is the greater, so the best cells come first
+                cells.sort_by(|a, b| opt.compare(b.0, a.0));
+                for (xi, (_, center)) in x.iter_mut().zip(&cells) {
+                    let cell = center.iter().zip(&x_ranges);
+                    let jitter = |(&c, &(lower, upper)): (&f64, &(f64, f64))| {
+                        // Inclusive, so that a range of a single value has no jitter
+                        let half = (upper - lower) / points as f64 / 2.0;
+                        F::of(c + rand::Rng::gen_range(r, -half..=half))
+                    };
+                    *xi = cell.map(jitter).collect();
+                }
+            }
+            let corners = init::corners(config.seed_corners.min(n), &x_ranges);
+            for (xi, corner) in x.iter_mut().zip(&corners) {
+                *xi = convert(corner);
+            }
+            let moved = x.iter_mut().zip(v.iter_mut()).take(replaced);
+            moved.for_each(|(xi, vi)| settle(xi, vi));
+            (x, v, value)
+        }
+    };
+    if config.init.is_some() {
+        x.iter_mut().zip(v.iter_mut()).for_each(|(xi, vi)| settle(xi, vi));
+    }
+    let value = x.iter().zip(value).map(|(xi, yi)| yi.unwrap_or_else(|| F::eval(f, xi)));
+    let value = value.collect();
+    let mut swarm = ParticleSwarm::evaluated(n, d, x, v, value, opt);
+    swarm.seed_particles(stream_seed);
+    swarm
+}
+
+/// Runs a single swarm from `seed`, or from `stream` when one goes through all runs; rows of
+/// repeated runs follow each other in the CSV output
+fn run_once<F: Scalar>(
+    config: &mut PsoConfig,
+    seed: u64,
+    stream: Option<&mut StdRng>,
+) -> Result<RunResult, RunError> {
+    let (n, d, opt) = (config.n, config.d, &config.opt);
+    let _span = info_span!("optimize", seed).entered();
+    let shared = stream.is_some();
+    let mut own = None;
+    let r = match stream {
+        Some(r) => r,
+        None => own.insert(StdRng::seed_from_u64(seed)),
+    };
+    // The particles and the noise have streams of their own, seeded like the run
+    let stream_seed = if shared { rand::Rng::gen(r) } else { seed };
+    let (interrupted, policy_at) = (config.interrupted_flag(), config.schedule());
+    let objective = &config.objective;
+    let penalty = config.penalty.as_ref();
+    // A stream of its own, so that the noise leaves the draws of the swarm as they are
+    let noise = config.noise.map(|sigma| {
+        let mut r = ChaCha12Rng::seed_from_u64(stream_seed);
+        r.set_stream(1);
+        (sigma, Mutex::new(r))
+    });
+    let evaluations = AtomicUsize::new(0);
+    let measured = |x: &[f64]| {
+        evaluations.fetch_add(1, atomic::Ordering::Relaxed);
+        match &noise {
+            None => objective(x),
+            Some((sigma, r)) => objective(x) + sigma * gaussian(&mut *r.lock().unwrap()),
+        }
+    };
+    let found_feasible = AtomicBool::new(false);
+    let penalized = |x: &[f64]| match penalty {
+        None => measured(x),
+        Some(p) => {
+            let cost = p.at(x);
+            if cost == 0.0 {
+                found_feasible.store(true, atomic::Ordering::Relaxed);
+            }
+            match opt {
+                OptimizationPolicy::FindMinimum => measured(x) + cost,
+                OptimizationPolicy::FindMaximum => measured(x) - cost,
+            }
+        }
+    };
+    let f = &penalized;
+    let bounds = config.bounds.as_ref();
+    if let Some(s) = config.cooperative {
+        let mut result = cooperative::run::<F>(config, s, f, &evaluations, r, seed)?;
+        if let Some(iterations) = config.polish {
+            let spent = evaluations.load(atomic::Ordering::Relaxed);
+            let budget = config.stop.evaluations.map(|budget| budget.saturating_sub(spent));
+            polish::apply(config, iterations, budget, f, &mut result);
+        }
+        result.evaluations = evaluations.load(atomic::Ordering::Relaxed);
+        if penalty.is_some() && !found_feasible.load(atomic::Ordering::Relaxed) {
+            return Err(RunError::InfeasibleSwarm);
+        }
+        let feasible = penalty.map(|p| p.feasible(&result.best_position));
+        return Ok(RunResult { feasible, ..result });
+    }
+
+    let warm_start = config.warm_start.take();
+    let resumed = warm_start.is_some();
+    // Iterations done, and since the global best last improved by more than the stagnation
+    // epsilon, against this value
+    let (mut swarm, mut k, mut stagnant, mut best_value, recent) = match warm_start {
+        Some(checkpoint) => {
+            let mut swarm = checkpoint.swarm.cast::<F>();
+            swarm.evaluate(f);
+            // The values lost with the checkpoint are not counted twice
+            evaluations.store(checkpoint.evaluations, atomic::Ordering::Relaxed);
+            let stagnation = (checkpoint.stagnant, checkpoint.stagnation_value);
+            (swarm, checkpoint.iteration, stagnation.0, stagnation.1, checkpoint.recent)
+        }
+        None => {
+            let swarm = initial_swarm(config, f, r, stream_seed);
+            let value = swarm.global_value();
+            (swarm, 0, 0, value, Vec::new())
+        }
+    };
+    let mut average = config.ma_window.map(|window| MovingAverage::new(window, &recent));
+    if let Some(weights) = &config.weights {
+        swarm.set_weights(weights.clone(), opt);
+    }
+    let failed = || config.program.as_ref().and_then(|p| p.take_error());
+    if let Some(reason) = failed() {
+        return Err(RunError::ExternalObjective(reason));
+    }
+    // Personal bests only improve, so a swarm with one valid position never loses it
+    if swarm.nan_evaluations == n {
+        return Err(RunError::AllNaN);
+    }
+    debug!(best_value = swarm.global_value(), "swarm initialized");
+    trace_swarm(&swarm, config.dump_swarm, config.digits);
+    if config.events {
+        let best = swarm.global_value();
+        Event::Init {
+            n,
+            d,
+            seed,
+            i: k,
+            best,
+        }
+        .emit();
+    }
+
+    let csv = &mut config.csv;
+    // A resumed run continues the trajectory of the run it came from
+    if !resumed {
+        write_csv_row(csv, 0, &swarm)?;
+    }
+    let mut history = History::default();
+    let on_iteration = &mut config.on_iteration;
+    let mut record = |k: usize, swarm: &ParticleSwarm<F>, average: Option<&MovingAverage>| {
+        if !config.record_history && on_iteration.is_none() && !config.events {
+            return ControlFlow::Continue(());
+        }
+        let stats = IterationStats::of(swarm, average);
+        if config.events {
+            Event::Iteration {
+                i: k,
+                best: stats.global_best,
+                mean_objective: stats.mean_objective,
+                velocity_norm: stats.velocity_norm,
+            }
+            .emit();
+        }
+        let flow = match on_iteration {
+            Some(callback) => callback(&stats),
+            None => ControlFlow::Continue(()),
+        };
+        if config.record_history {
+            history.iterations.push(stats);
+        }
+        flow
+    };
+
+    let stalled = |swarm: &ParticleSwarm<F>| match config.vel_tol {
+        Some(tol) => mean_velocity(swarm) < tol,
+        None => false,
+    };
+
+    let mut collapsed_at = None;
+    let start = Instant::now();
+    let max_iterations = config.stop.iterations;
+    let progress = match max_iterations {
+        Some(i) if config.progress && !config.events => ProgressBar::new(i as u64),
+        _ => ProgressBar::hidden(),
+    };
+    progress.set_style(ProgressStyle::with_template("{bar:40} {pos}/{len} {msg}").unwrap());
+    progress.set_position(k as u64);
+    let spent = || evaluations.load(atomic::Ordering::Relaxed);
+    // Evaluations of the last iteration apart from re-evaluations, and the count before it; an
+    // iteration updates every particle once, which the first one is expected to do
+    let (mut per_iteration, mut mark) = (n, spent());
+    // Re-evaluating the personal bests and the best ever takes one evaluation each
+    let reevaluates = |k: usize| config.reeval_every.is_some_and(|every| k.is_multiple_of(every));
+    let reevaluation = |k: usize| if reevaluates(k) { n + 1 } else { 0 };
+    let target = config.stop.target;
+    let reached = |value: f64| target.is_some_and(|t| (value - t.target).abs() <= t.tol);
+    let stop_reason = loop {
+        if reached(swarm.global_value()) {
+            break StopReason::Target;
+        }
+        if max_iterations.is_some_and(|i| k >= i) {
+            break StopReason::Iterations;
+        }
+        let checks_time = k % TIME_CHECK_INTERVAL == 0;
+        if checks_time && config.stop.time.is_some_and(|t| start.elapsed() >= t) {
+            break StopReason::TimeLimit;
+        }
+        let now = spent();
+        if now > mark {
+            (per_iteration, mark) = ((now - mark).saturating_sub(reevaluation(k)), now);
+        }
+        let next = now + per_iteration + reevaluation(k + 1);
+        if config.stop.evaluations.is_some_and(|budget| next > budget) {
+            break StopReason::EvaluationBudget;
+        }
+        if interrupted() {
+            break StopReason::Interrupted;
+        }
+        k += 1;
+
+        let consts = policy_at(k);
+        let pull = update(&mut swarm, &consts, bounds, &config.integer_dims, &config.fixed, f, opt);
+        if reevaluates(k) {
+            swarm.reevaluate_bests(f, opt, consts.elitism);
+        }
+        if let Some(reason) = failed() {
+            return Err(RunError::ExternalObjective(reason));
+        }
+        if swarm.position.iter().all(|x| x.iter().any(|xi| !xi.is_finite())) {
+            return Err(RunError::Diverged);
+        }
+        write_csv_row(csv, k, &swarm)?;
+        if let Some(average) = &mut average {
+            average.push(swarm.global_value());
+        }
+        let flow = record(k, &swarm, average.as_ref());
+        progress.set_message(format!("best value {}", swarm.best_ever.1));
+        progress.inc(1);
+        let (cognitive, social) = (pull.cognitive, pull.social);
+        debug!(iteration = k, best_value = swarm.global_value(), cognitive, social, "iteration");
+        if let VelocityModel::Gcpso = consts.model {
+            debug!(rho = swarm.radius.rho, "search radius");
+        }
+        if let Some(average) = &average {
+            debug!(moving_average = average.mean(), "moving average");
+        }
+        trace_swarm(&swarm, config.dump_swarm, config.digits);
+        if flow.is_break() {
+            break StopReason::Callback;
+        }
+
+        if let Some(t) = target {
+            let value = match &average {
+                Some(average) if t.smoothed => average.mean(),
+                _ => swarm.global_value(),
+            };
+            let improvement = match opt {
+                OptimizationPolicy::FindMinimum => best_value - value,
+                OptimizationPolicy::FindMaximum => value - best_value,
+            };
+            if improvement > t.stag_eps {
+                best_value = value;
+                stagnant = 0;
+            } else {
+                stagnant += 1;
+            }
+            if stagnant >= t.stag_window {
+                break StopReason::Stagnation;
+            }
+        }
+        if stalled(&swarm) {
+            break StopReason::VelocityTolerance;
+        }
+        let collapsed = |eps: f64| diameter(&swarm.position) < eps;
+        if !reached(swarm.global_value()) && config.diameter_eps.is_some_and(collapsed) {
+            collapsed_at.get_or_insert(k);
+            if config.diameter_reinit {
+                swarm.scatter();
+            }
+        }
+        if let Some(path) = &config.checkpoint {
+            if k % config.checkpoint_every == 0 {
+                let checkpoint = Checkpoint {
+                    iteration: k,
+                    swarm: swarm.cast(),
+                    stagnant,
+                    stagnation_value: best_value,
+                    recent: average.as_ref().map(MovingAverage::values).unwrap_or_default(),
+                    evaluations: spent(),
+                };
+                checkpoint.write(path)?;
+            }
+        }
+    };
+    progress.finish_and_clear();
+    let iterations = k;
+    debug!(iterations, best_value = swarm.best_ever.1, ?stop_reason, "run finished");
+    if config.events {
+        let best = swarm.best_ever.1;
+        Event::Done {
+            iterations,
+            best,
+            stop_reason,
+        }
+        .emit();
+    }
+
+    let elapsed_secs = start.elapsed().as_secs_f64();
+    let spread = Spread::of(&swarm.position, &swarm.best_ever.0, config.conv_radius);
+    if penalty.is_some() && !found_feasible.load(atomic::Ordering::Relaxed) {
+        return Err(RunError::InfeasibleSwarm);
+    }
+    let mut result = RunResult {
+        iterations,
+        elapsed_secs,
+        iterations_per_sec: if elapsed_secs > 0.0 {
+            iterations as f64 / elapsed_secs
+        } else {
+            0.0
+        },
+        best_value: swarm.best_ever.1,
+        best_position: convert(&swarm.best_ever.0),
+        seed,
+        converged: !matches!(
+            stop_reason,
+            StopReason::Iterations
+                | StopReason::TimeLimit
+                | StopReason::Interrupted
+                | StopReason::EvaluationBudget
+        ),
+        stop_reason,
+        nan_evaluations: swarm.nan_evaluations,
+        evaluations: 0,
+        spread,
+        final_position: swarm.position.iter().map(|x| convert(x)).collect(),
+        collapsed_at,
+        feasible: None,
+        history: config.record_history.then_some(history),
+        pre_polish_value: None,
+        position_error: None,
+        value_gap: None,
+        objective_values: None,
+    };
+    if let Some(iterations) = config.polish {
+        let budget = config.stop.evaluations.map(|budget| budget.saturating_sub(spent()));
+        polish::apply(config, iterations, budget, f, &mut result);
+    }
+    result.evaluations = spent();
+    result.feasible = penalty.map(|p| p.feasible(&result.best_position));
+    Ok(result)
+}
+
+/// A swarm that the caller advances one iteration at a time, e.g. to draw every iteration in an
+/// interactive front end; from the same seed it goes through the same iterations as the first run
+/// of [`run`]
+///
+/// Only the swarm moves: stopping, restarts, the polish, diameter reinitialization and the outputs
+/// of the configuration (CSV rows, checkpoints, events, the progress bar and the iteration
+/// callback) are up to the caller.
+pub struct Optimizer {
+    config: PsoConfig,
+    swarm: ParticleSwarm,
+    iteration: usize,
+    average: Option<MovingAverage>,
+    evaluations: Arc<AtomicUsize>,
+}
+
+impl Optimizer {
+    /// Validates `config` and initializes its swarm, or continues its warm start
+    pub fn new(mut config: PsoConfig) -> Result<Self, RunError> {
+        validate(&config)?;
+        // These change the objective or the swarm beyond a single swarm of f64 positions
+        let unsupported = [
+            (config.cooperative.is_some(), "cooperative sub-swarms"),
+            (matches!(config.precision, Precision::F32), "f32 precision"),
+            (config.noise.is_some(), "objective noise"),
+            (config.penalty.is_some(), "a penalty"),
+        ];
+        if let Some((_, what)) = unsupported.iter().find(|(used, _)| *used) {
+            let reason = format!("a stepped swarm does not support {}", what);
+            return Err(RunError::InvalidConfig(reason));
+        }
+
+        // Counted as in `run`
+        let evaluations = Arc::new(AtomicUsize::new(0));
+        let objective = mem::replace(&mut config.objective, Box::new(|_| f64::NAN));
+        let counter = Arc::clone(&evaluations);
+        config.objective = Box::new(move |x| {
+            counter.fetch_add(1, atomic::Ordering::Relaxed);
+            objective(x)
+        });
+        let seed = config.seeds.first().copied().unwrap_or(config.seed);
+        let (swarm, iteration, recent) = match config.warm_start.take() {
+            Some(checkpoint) => {
+                let mut swarm = checkpoint.swarm;
+                swarm.evaluate(&*config.objective);
+                evaluations.store(checkpoint.evaluations, atomic::Ordering::Relaxed);
+                (swarm, checkpoint.iteration, checkpoint.recent)
+            }
+            None => {
+                // A stream through all runs seeds the particles with its first draw, as in `run`
+                let (mut r, stream_seed) = match config.resume_rng {
+                    true => {
+                        let mut r = StdRng::seed_from_u64(config.seed);
+                        let stream_seed = rand::Rng::gen(&mut r);
+                        (r, stream_seed)
+                    }
+                    false => (StdRng::seed_from_u64(seed), seed),
+                };
+                let swarm = initial_swarm(&config, &*config.objective, &mut r, stream_seed);
+                (swarm, 0, Vec::new())
+            }
+        };
+        let average = config.ma_window.map(|window| MovingAverage::new(window, &recent));
+        let mut optimizer = Self {
+            config,
+            swarm,
+            iteration,
+            average,
+            evaluations,
+        };
+        if let Some(weights) = &optimizer.config.weights {
+            optimizer.swarm.set_weights(weights.clone(), &optimizer.config.opt);
+        }
+        optimizer.check()?;
+        if optimizer.swarm.nan_evaluations == optimizer.config.n {
+            return Err(RunError::AllNaN);
+        }
+        Ok(optimizer)
+    }
+
+    /// Moves every particle once, returning the swarm afterwards
+    pub fn step(&mut self) -> Result<IterationStats, RunError> {
+        self.iteration += 1;
+        let (config, swarm, k) = (&self.config, &mut self.swarm, self.iteration);
+        let (f, opt) = (&*config.objective, &config.opt);
+        let consts = config.policy_at(k);
+        let bounds = config.bounds.as_ref();
+        update(swarm, &consts, bounds, &config.integer_dims, &config.fixed, f, opt);
+        if config.reeval_every.is_some_and(|every| k % every == 0) {
+            swarm.reevaluate_bests(f, opt, consts.elitism);
+        }
+        self.check()?;
+        if self.swarm.position.iter().all(|x| x.iter().any(|xi| !xi.is_finite())) {
+            return Err(RunError::Diverged);
+        }
+        if let Some(average) = &mut self.average {
+            average.push(self.swarm.global_value());
+        }
+        Ok(IterationStats::of(&self.swarm, self.average.as_ref()))
+    }
+
+    /// Position and value of the best position found so far
+    pub fn best(&self) -> (&[f64], f64) {
+        (&self.swarm.best_ever.0, self.swarm.best_ever.1)
+    }
+
+    /// Iterations done so far
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// Evaluations of the objective so far, see [`RunResult::evaluations`]
+    pub fn evaluations(&self) -> usize {
+        self.evaluations.load(atomic::Ordering::Relaxed)
+    }
+
+    pub fn swarm(&self) -> &ParticleSwarm {
+        &self.swarm
+    }
+
+    /// Fails if the objective program did
+    fn check(&self) -> Result<(), RunError> {
+        match self.config.program.as_ref().and_then(|p| p.take_error()) {
+            Some(reason) => Err(RunError::ExternalObjective(reason)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sphere(x: &[f64]) -> f64 {
+        x.iter().map(|xi| xi * xi).sum()
+    }
+
+    /// A wide bowl around zero, with a far narrower and deeper pit near the edge of `[-5, 5]`
+    fn trap(x: &[f64]) -> f64 {
+        if (x[0] - 4.5).abs() < 1e-6 {
+            -1.0
+        } else {
+            sphere(x)
+        }
+    }
+
+    /// `n` particles of `d` coordinates on `objective` in `[-5, 5]`, for 100 iterations from seed 1
+    pub(crate) fn config(
+        n: usize,
+        d: usize,
+        objective: impl Fn(&[f64]) -> f64 + Sync + 'static,
+    ) -> PsoConfig {
+        PsoConfig {
+            n,
+            d,
+            policy: UpdatePolicy::new(0.729, 1.49445, 1.49445),
+            w_schedule: None,
+            acc_schedule: None,
+            opt: OptimizationPolicy::FindMinimum,
+            stop: StoppingRule {
+                iterations: Some(100),
+                target: None,
+                time: None,
+                evaluations: None,
+            },
+            vel_tol: None,
+            ma_window: None,
+            diameter_eps: None,
+            diameter_reinit: false,
+            conv_radius: 0.1,
+            cooperative: None,
+            weights: None,
+            bounds: Some(Bounds::uniform(-5.0, 5.0, d, BoundMode::Clamp)),
+            integer_dims: Vec::new(),
+            fixed: Vec::new(),
+            objective: Box::new(objective),
+            penalty: None,
+            noise: None,
+            reeval_every: None,
+            polish: None,
+            program: None,
+            init_range: None,
+            init_method: InitMethod::Uniform,
+            seed_corners: 0,
+            obl_init: false,
+            grid_seed: None,
+            vinit_range: None,
+            init: None,
+            vinit: None,
+            precision: Precision::F64,
+            seed: 1,
+            seeds: Vec::new(),
+            restarts: 1,
+            resume_rng: false,
+            csv: None,
+            record_history: false,
+            progress: false,
+            dump_swarm: false,
+            digits: None,
+            events: false,
+            on_iteration: None,
+            checkpoint: None,
+            checkpoint_every: 1,
+            warm_start: None,
+            interrupt: None,
+        }
+    }
+
+    #[test]
+    fn better_values_compare_greater() {
+        let (min, max) = (OptimizationPolicy::FindMinimum, OptimizationPolicy::FindMaximum);
+        assert_eq!(min.compare(1.0, 2.0), Ordering::Greater);
+        assert!(min.better(-3.0, 0.5) && !min.better(0.5, -3.0));
+        assert_eq!(max.compare(1.0, 2.0), Ordering::Less);
+        assert!(max.better(0.5, -3.0) && !max.better(-3.0, 0.5));
+        assert!(!min.better(1.0, 1.0) && !max.better(1.0, 1.0));
+        for opt in [min, max] {
+            assert!(opt.better(f64::INFINITY, f64::NAN) && opt.better(f64::NEG_INFINITY, f64::NAN));
+            assert!(!opt.better(f64::NAN, 0.0) && !opt.better(f64::NAN, f64::NAN));
+            assert_eq!(opt.compare(f64::NAN, f64::NAN), Ordering::Equal);
+        }
+    }
+
+    #[test]
+    fn lower_index_wins_ties() {
+        let (min, max) = (OptimizationPolicy::FindMinimum, OptimizationPolicy::FindMaximum);
+        assert_eq!(best_of(&[1.0, 0.5, 0.5, 2.0], &[], &min), 1);
+        assert_eq!(best_of(&[3.0, 1.0, 3.0], &[], &max), 0);
+        assert_eq!(best_of(&[f64::NAN, f64::NAN, f64::NAN], &[], &min), 0);
+        assert_eq!(best_of(&[f64::NAN, 4.0, 4.0], &[], &min), 1);
+    }
+
+    #[test]
+    fn initial_global_best_is_the_best_position() {
+        let x = vec![vec![2.0, 1.0], vec![-0.5, 0.25], vec![1.0, -3.0]];
+        let v = vec![vec![0.0; 2]; 3];
+        let opt = OptimizationPolicy::FindMinimum;
+        let swarm: ParticleSwarm = ParticleSwarm::new(3, 2, x.clone(), v.clone(), &sphere, &opt);
+        assert_eq!(swarm.best_index, 1);
+        assert_eq!(swarm.global_optimum.as_ref(), Some(&x[1]));
+        let opt = OptimizationPolicy::FindMaximum;
+        let swarm: ParticleSwarm = ParticleSwarm::new(3, 2, x.clone(), v, &sphere, &opt);
+        assert_eq!(swarm.best_index, 2);
+        assert_eq!(swarm.best_ever, (x[2].clone(), 10.0));
+    }
+
+    #[test]
+    fn opposition_never_starts_a_particle_worse() {
+        let shifted = |x: &[f64]| (x[0] - 3.0).powi(2);
+        let values = |obl_init: bool| {
+            let mut opposed = config(8, 1, shifted);
+            opposed.obl_init = obl_init;
+            let mut r = StdRng::seed_from_u64(5);
+            let swarm: ParticleSwarm = initial_swarm(&opposed, &shifted, &mut r, 5);
+            swarm.value
+        };
+        let (plain, opposed) = (values(false), values(true));
+        assert!(plain.iter().zip(&opposed).all(|(y, z)| z <= y));
+        assert!(plain.iter().zip(&opposed).any(|(y, z)| z < y));
+    }
+
+    #[test]
+    fn opposition_keeps_the_better_half_of_a_monotone_objective() {
+        let line = |x: &[f64]| x[0];
+        let mut opposed = config(20, 1, line);
+        opposed.init_range = Some((-1.0, 1.0));
+        opposed.obl_init = true;
+        let mut r = StdRng::seed_from_u64(5);
+        let swarm: ParticleSwarm = initial_swarm(&opposed, &line, &mut r, 5);
+        // The opposite of `x` across `[-1, 1]` is `-x`, the lower of which is kept
+        assert!(swarm.position.iter().all(|x| x[0] <= 0.0));
+    }
+
+    #[test]
+    fn resumed_stream_starts_every_repeat_from_a_new_swarm() {
+        let starts = |resume_rng: bool| {
+            let mut repeated = config(5, 2, sphere);
+            repeated.stop.iterations = Some(1);
+            repeated.seeds = vec![7, 7];
+            repeated.resume_rng = resume_rng;
+            let results = run_repeats(repeated, 2).unwrap();
+            (results[0].final_position.clone(), results[1].final_position.clone())
+        };
+        let (first, second) = starts(false);
+        assert_eq!(first, second);
+        let (first, second) = starts(true);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn light_particle_with_a_negative_value_is_not_the_best() {
+        let opt = OptimizationPolicy::FindMinimum;
+        let x = vec![vec![0.0], vec![1.0]];
+        let v = vec![vec![0.0], vec![0.0]];
+        let shifted = |x: &[f64]| x[0] - 5.0;
+        let mut swarm: ParticleSwarm = ParticleSwarm::new(2, 1, x, v, &shifted, &opt);
+        assert_eq!(swarm.best_index, 0);
+        swarm.set_weights(vec![0.01, 1.0], &opt);
+        assert_eq!(swarm.best_index, 1);
+        swarm.set_weights(vec![1.0, 0.01], &OptimizationPolicy::FindMaximum);
+        assert_eq!(swarm.best_index, 0);
+    }
+
+    #[test]
+    fn summary_matches_the_statistics_by_hand() {
+        let opt = OptimizationPolicy::FindMinimum;
+        let x = vec![vec![0.0, 0.0], vec![3.0, 4.0], vec![3.0, 0.0], vec![2.0, 0.0]];
+        let v = vec![vec![3.0, 4.0], vec![0.0, 0.0], vec![0.0, -6.0], vec![-8.0, 6.0]];
+        let swarm: ParticleSwarm = ParticleSwarm::new(4, 2, x, v, &sphere, &opt);
+        let s = swarm.summary();
+        assert_eq!((s.best_position, s.best_value, s.best_index), (vec![0.0, 0.0], 0.0, 0));
+        assert_eq!((s.min, s.mean, s.max), (vec![0.0, 0.0], vec![2.0, 1.0], vec![3.0, 4.0]));
+        assert_eq!(s.mean_speed, [2.75, 4.0]);
+        assert_eq!(s.mean_objective, (0.0 + 25.0 + 9.0 + 4.0) / 4.0);
+        assert_eq!(s.velocity_norm, (5.0 + 0.0 + 6.0 + 10.0) / 4.0);
+        assert_eq!(s.diameter, 5.0);
+    }
+
+    #[test]
+    fn moving_average_is_the_mean_of_the_window() {
+        let mut average = MovingAverage::new(3, &[]);
+        assert!(average.mean().is_nan());
+        let means = [1.0, 1.5, 2.0, 3.0, 6.0, 10.0];
+        for (value, mean) in [1.0, 2.0, 3.0, 4.0, 11.0, 15.0].into_iter().zip(means) {
+            average.push(value);
+            assert_eq!(average.mean(), mean);
+        }
+        assert_eq!(average.values(), [4.0, 11.0, 15.0]);
+        let restored = MovingAverage::new(3, &[1.0, 2.0, 3.0, 4.0, 11.0, 15.0]);
+        assert_eq!(restored.values(), average.values());
+    }
+
+    #[test]
+    fn display_lists_the_statistics_of_every_coordinate() {
+        let opt = OptimizationPolicy::FindMinimum;
+        let x = vec![vec![1.0, 4.0], vec![3.0, -2.0]];
+        let v = vec![vec![1.0, -1.0], vec![-3.0, 1.0]];
+        let swarm: ParticleSwarm = ParticleSwarm::new(2, 2, x, v, &sphere, &opt);
+        let text = swarm.to_string();
+        let lines = text.lines().collect::<Vec<&str>>();
+        assert_eq!(lines[0], "Global best: 13 at [3.0, -2.0]");
+        let row = |i: usize| lines[i].split_whitespace().collect::<Vec<&str>>();
+        assert_eq!(row(1), ["coordinate", "min", "mean", "max", "mean", "|v|"]);
+        assert_eq!(row(2), ["0", "1.00000e0", "2.00000e0", "3.00000e0", "2.00000e0"]);
+        assert_eq!(row(3), ["1", "-2.00000e0", "1.00000e0", "4.00000e0", "1.00000e0"]);
+        let text = format!("{:.1}", swarm);
+        assert_eq!(text.lines().nth(3).unwrap().split_whitespace().nth(2), Some("1.0e0"));
+    }
+
+    #[test]
+    fn wrapped_coordinates_come_back_in_on_the_other_side() {
+        let bounds = Bounds::uniform(0.0, 10.0, 1, BoundMode::Wrap);
+        for (x, wrapped) in [(12.0, 2.0), (-3.0, 7.0), (25.0, 5.0), (-21.0, 9.0), (4.0, 4.0)] {
+            let (mut x, mut v) = (x, 1.5);
+            bounds.apply(0, &mut x, &mut v);
+            assert_eq!((x, v), (wrapped, 1.5));
+        }
+    }
+
+    #[test]
+    fn velocities_stay_within_vmax() {
+        let opt = OptimizationPolicy::FindMinimum;
+        let x = vec![vec![3.0, -2.0], vec![-1.0, 4.0], vec![0.5, 0.5]];
+        let v = vec![vec![1e6, -1e6]; 3];
+        let mut swarm: ParticleSwarm = ParticleSwarm::new(3, 2, x, v, &sphere, &opt);
+        let consts = UpdatePolicy {
+            vmax: Some(0.5),
+            ..UpdatePolicy::new(0.729, 1.49445, 1.49445)
+        };
+        for _ in 0..50 {
+            update(&mut swarm, &consts, None, &[], &[], &sphere, &opt);
+            assert!(swarm.velocity.iter().flatten().all(|v| v.abs() <= 0.5));
+        }
+    }
+
+    #[test]
+    fn update_evaluates_every_new_position_once() {
+        let opt = OptimizationPolicy::FindMinimum;
+        let evaluated = std::sync::Mutex::new(Vec::new());
+        let counted = |x: &[f64]| {
+            evaluated.lock().unwrap().push(x.to_vec());
+            sphere(x)
+        };
+        let x = vec![vec![3.0, -2.0], vec![-1.0, 4.0], vec![0.5, 0.5]];
+        let v = vec![vec![0.5, 0.25], vec![-0.5, 1.0], vec![1.0, -1.0]];
+        let mut swarm: ParticleSwarm = ParticleSwarm::new(3, 2, x, v, &counted, &opt);
+        let consts = UpdatePolicy::new(0.729, 1.49445, 1.49445);
+        for _ in 0..5 {
+            evaluated.lock().unwrap().clear();
+            let local_optimum = swarm.local_optimum.clone();
+            update(&mut swarm, &consts, None, &[], &[], &counted, &opt);
+            let mut evaluated = evaluated.lock().unwrap().clone();
+            assert_eq!(evaluated.len(), 3);
+            assert!(evaluated.iter().all(|x| !local_optimum.contains(x)));
+            let mut position = swarm.position.clone();
+            evaluated.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            position.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert_eq!(evaluated, position);
+        }
+    }
+
+    #[test]
+    fn ablated_terms_pull_nothing() {
+        let (x, best, social) = ([0.0, 0.0], [1.0, 1.0], [2.0, -1.0]);
+        // FIPS is constricted, which takes c1 + c2 above 4
+        let full = UpdatePolicy::new(0.5, 2.05, 2.05);
+        let alone = |cognitive: bool, social: bool| UpdatePolicy {
+            cognitive,
+            social,
+            ..full
+        };
+        let mut v = [1.0, 2.0];
+        let pull = accelerate(&mut v, &x, &best, &social, &alone(false, true), || 0.5);
+        assert_eq!(pull.cognitive, 0.0);
+        assert_eq!(v, [0.5 + 2.05, 1.0 - 1.025]);
+        let mut v = [1.0, 2.0];
+        let pull = accelerate(&mut v, &x, &best, &social, &alone(true, false), || 0.5);
+        assert_eq!(pull.social, 0.0);
+        assert_eq!(v, [0.5 + 1.025, 1.0 + 1.025]);
+
+        let opt = OptimizationPolicy::FindMinimum;
+        let mut r = StdRng::seed_from_u64(4);
+        let ranges = [(-5.0, 5.0); 2];
+        let mut swarm: ParticleSwarm =
+            ParticleSwarm::new_random(6, 2, &ranges, (-1.0, 1.0), &sphere, &opt, &mut r);
+        for model in [VelocityModel::Inertia, VelocityModel::Fips] {
+            for _ in 0..5 {
+                let consts = UpdatePolicy { model, ..alone(false, true) };
+                let pull = update(&mut swarm, &consts, None, &[], &[], &sphere, &opt);
+                assert!(pull.cognitive == 0.0 && pull.social > 0.0);
+                let consts = UpdatePolicy { model, ..alone(true, false) };
+                let pull = update(&mut swarm, &consts, None, &[], &[], &sphere, &opt);
+                assert!(pull.social == 0.0 && pull.cognitive > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn antithetic_partners_draw_one_minus_each_other() {
+        let opt = OptimizationPolicy::FindMinimum;
+        // With no inertia and a pull of 1 towards the personal bests, each velocity is `r1` or `r2`
+        for (c1, c2) in [(1.0, 0.0), (0.0, 1.0)] {
+            let x = vec![vec![0.0; 3]; 4];
+            let mut swarm: ParticleSwarm = ParticleSwarm::new(4, 3, x.clone(), x, &sphere, &opt);
+            swarm.local_optimum = vec![vec![1.0; 3]; 4];
+            swarm.seed_particles(9);
+            let consts = UpdatePolicy {
+                antithetic: true,
+                ..UpdatePolicy::new(0.0, c1, c2)
+            };
+            update(&mut swarm, &consts, None, &[], &[], &sphere, &opt);
+            for pair in swarm.velocity.chunks(2) {
+                assert!(pair[0].iter().zip(&pair[1]).all(|(r, s)| (r + s - 1.0).abs() < 1e-12));
+                assert_ne!(pair[0], pair[1]);
+            }
+        }
+    }
+
+    #[test]
+    fn mutation_draws_from_the_particle_stream() {
+        let mutated = |mutation: f64| {
+            let consts = UpdatePolicy {
+                mutation,
+                mutation_scale: 0.5,
+                ..UpdatePolicy::new(0.729, 1.49445, 1.49445)
+            };
+            let mut r = StdRng::seed_from_u64(8);
+            let mut v = [0.25, -0.5, 1.0];
+            perturb(&mut v, &consts, &mut r);
+            (v, rand::Rng::gen::<u64>(&mut r))
+        };
+        let (v, after) = mutated(1.0);
+        assert!(v.iter().zip([0.25, -0.5, 1.0]).all(|(vj, before)| *vj != before));
+        assert_eq!(mutated(1.0), (v, after));
+        let (v, after) = mutated(0.0);
+        assert_eq!(v, [0.25, -0.5, 1.0]);
+        assert_eq!(after, rand::Rng::gen::<u64>(&mut StdRng::seed_from_u64(8)));
+    }
+
+    #[test]
+    fn pulls_are_non_negative() {
+        let opt = OptimizationPolicy::FindMinimum;
+        let mut r = StdRng::seed_from_u64(3);
+        let ranges = [(-5.0, 5.0); 3];
+        let mut swarm: ParticleSwarm =
+            ParticleSwarm::new_random(8, 3, &ranges, (-1.0, 1.0), &sphere, &opt, &mut r);
+        let consts = UpdatePolicy::new(0.729, 1.49445, 1.49445);
+        for _ in 0..20 {
+            let pull = update(&mut swarm, &consts, None, &[], &[], &sphere, &opt);
+            assert!(pull.cognitive >= 0.0 && pull.social >= 0.0);
+        }
+        let pull = update(&mut swarm, &consts, None, &[], &[], &sphere, &opt);
+        assert!(pull.cognitive > 0.0 || pull.social > 0.0);
+    }
+
+    #[test]
+    fn random_search_leaves_the_velocities() {
+        let opt = OptimizationPolicy::FindMinimum;
+        let x = vec![vec![3.0, -2.0], vec![-1.0, 4.0]];
+        let v = vec![vec![0.25, -0.5], vec![1.0, 2.0]];
+        let mut swarm: ParticleSwarm = ParticleSwarm::new(2, 2, x, v.clone(), &sphere, &opt);
+        let consts = UpdatePolicy {
+            model: VelocityModel::RandomSearch,
+            ..UpdatePolicy::new(0.729, 1.49445, 1.49445)
+        };
+        let bounds = Bounds::uniform(-5.0, 5.0, 2, BoundMode::Clamp);
+        for _ in 0..10 {
+            let position = swarm.position.clone();
+            update(&mut swarm, &consts, Some(&bounds), &[], &[], &sphere, &opt);
+            assert_ne!(swarm.position, position);
+            assert_eq!(swarm.velocity, v);
+        }
+    }
+
+    #[test]
+    fn neighbors_follow_the_topology() {
+        assert_eq!(neighbors(&Topology::Gbest, 2, 4), [0, 1, 3]);
+        assert_eq!(neighbors(&Topology::Ring { k: 2 }, 0, 5), [4, 1]);
+        assert_eq!(neighbors(&Topology::Ring { k: 4 }, 4, 5), [3, 0, 2, 1]);
+        // A 3 by 3 grid wraps around both ways
+        assert_eq!(neighbors(&Topology::VonNeumann, 0, 9), [6, 3, 2, 1]);
+        assert_eq!(neighbors(&Topology::VonNeumann, 4, 9), [1, 7, 3, 5]);
+        // The empty cells of a partial last row are skipped
+        assert_eq!(neighbors(&Topology::VonNeumann, 1, 7), [4, 4, 0, 2]);
+    }
+
+    #[test]
+    fn fully_informed_pull_shares_the_ring() {
+        let topology = Topology::Ring { k: 2 };
+        let consts = UpdatePolicy {
+            model: VelocityModel::Fips,
+            ..UpdatePolicy::new(0.729, 2.05, 2.05)
+        };
+        let local_optimum = [[1.0], [2.0], [-5.0], [3.0]];
+        let informants = std::iter::once(0)
+            .chain(neighbors(&topology, 0, 4))
+            .map(|k| &local_optimum[k][..])
+            .collect::<Vec<&[f64]>>();
+        assert_eq!(informants, [&[1.0][..], &[3.0], &[2.0]]);
+        let (mut v, mut r) = ([0.0], StdRng::seed_from_u64(1));
+        let pull = fully_informed(&mut v, &[0.0], &informants, &consts, &mut r);
+        // Every informant lies ahead of the particle, so both shares push it forward
+        assert!(pull.cognitive > 0.0 && pull.social > 0.0);
+        let chi = consts.constriction();
+        assert!((v[0] - chi * (pull.cognitive + pull.social)).abs() < 1e-12);
+        // At most the full pull of all three
+        assert!(v[0] <= chi * (2.05 + 2.05) * (1.0 + 3.0 + 2.0) / 3.0);
+    }
+
+    #[test]
+    fn bare_bones_samples_around_the_midpoint() {
+        // Zero at both personal bests and positive elsewhere, so that neither ever moves
+        let f = |x: &[f64]| (x[0] * (x[0] - 2.0)).abs();
+        let opt = OptimizationPolicy::FindMinimum;
+        let (x, v) = (vec![vec![0.0], vec![2.0]], vec![vec![0.0]; 2]);
+        let mut swarm: ParticleSwarm = ParticleSwarm::new(2, 1, x, v, &f, &opt);
+        let consts = UpdatePolicy {
+            model: VelocityModel::BareBones,
+            ..UpdatePolicy::new(0.729, 1.49445, 1.49445)
+        };
+        let samples = 4000;
+        let mut sum = 0.0;
+        for _ in 0..samples {
+            update(&mut swarm, &consts, None, &[], &[], &f, &opt);
+            sum += swarm.position[1][0];
+        }
+        assert_eq!(swarm.local_optimum, [[0.0], [2.0]]);
+        assert!((sum / samples as f64 - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn stale_particle_is_drawn_anew() {
+        // Resting at the minimum, the particle never improves its personal best
+        let opt = OptimizationPolicy::FindMinimum;
+        let (x, v) = (vec![vec![0.0]], vec![vec![0.0]]);
+        let mut swarm: ParticleSwarm = ParticleSwarm::new(1, 1, x, v, &sphere, &opt);
+        let consts = UpdatePolicy {
+            reinit_after: Some(3),
+            ..UpdatePolicy::new(0.729, 1.49445, 1.49445)
+        };
+        let bounds = Bounds::uniform(-5.0, 5.0, 1, BoundMode::Clamp);
+        for k in 1..=3 {
+            update(&mut swarm, &consts, Some(&bounds), &[], &[], &sphere, &opt);
+            assert_eq!((swarm.position[0][0], swarm.stale[0]), (0.0, k));
+        }
+        update(&mut swarm, &consts, Some(&bounds), &[], &[], &sphere, &opt);
+        assert_ne!(swarm.position[0][0], 0.0);
+        assert_eq!(swarm.local_optimum[0][0], 0.0);
+        assert_eq!(swarm.stale[0], 1);
+    }
+
+    #[test]
+    fn charged_particles_stay_apart() {
+        let opt = OptimizationPolicy::FindMinimum;
+        let x = vec![vec![1.0, 1.0], vec![-2.0, 0.5], vec![-1.0, 0.5], vec![3.0, -1.0]];
+        let v = vec![vec![0.0; 2]; 4];
+        let mut swarm: ParticleSwarm = ParticleSwarm::new(4, 2, x, v, &sphere, &opt);
+        let consts = UpdatePolicy {
+            model: VelocityModel::Charged,
+            ..UpdatePolicy::new(0.729, 1.49445, 1.49445)
+        };
+        let bounds = Bounds::uniform(-5.0, 5.0, 2, BoundMode::Clamp);
+        let mut closest = f64::INFINITY;
+        for _ in 0..300 {
+            update(&mut swarm, &consts, Some(&bounds), &[], &[], &sphere, &opt);
+            let (a, b) = (&swarm.position[0], &swarm.position[2]);
+            closest = closest.min(((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt());
+        }
+        // Particles 0 and 2 carry the charge, while the neutral ones close in on the minimum
+        assert!(closest > 0.1, "{}", closest);
+        assert!(sphere(&swarm.local_optimum[swarm.best_index]) < 1e-3);
+    }
+
+    #[test]
+    fn gcpso_best_particle_keeps_moving() {
+        let opt = OptimizationPolicy::FindMinimum;
+        let x = vec![vec![1.0, 1.0], vec![-2.0, 0.5], vec![3.0, -1.0]];
+        let v = vec![vec![0.0; 2]; 3];
+        let mut swarm: ParticleSwarm = ParticleSwarm::new(3, 2, x, v, &sphere, &opt);
+        let consts = UpdatePolicy {
+            model: VelocityModel::Gcpso,
+            vmax: Some(1e-3),
+            ..UpdatePolicy::new(0.729, 1.49445, 1.49445)
+        };
+        update(&mut swarm, &consts, None, &[], &[], &sphere, &opt);
+        for _ in 0..10 {
+            // Sent to search within the radius, far beyond the velocity limit of the others
+            let best = swarm.best_index;
+            assert!(swarm.velocity[best].iter().any(|v| v.abs() > 1e-3));
+            let before = swarm.position[best].clone();
+            update(&mut swarm, &consts, None, &[], &[], &sphere, &opt);
+            let moved = swarm.position[best].iter().zip(&before).map(|(a, b)| (a - b).abs());
+            assert!(moved.fold(0.0, f64::max) > 1e-3);
+        }
+    }
+
+    #[test]
+    fn linear_schedule_is_halfway_at_the_midpoint() {
+        let schedule = LinearSchedule { start: 0.9, end: 0.4 };
+        assert_eq!(schedule.at(1, 11), 0.9);
+        assert!((schedule.at(6, 11) - 0.65).abs() < 1e-12);
+        assert!((schedule.at(11, 11) - 0.4).abs() < 1e-12);
+    }
+
+    #[test]
+    fn tvac_trades_the_cognitive_for_the_social_pull() {
+        let mut config = config(4, 1, sphere);
+        config.stop.iterations = Some(11);
+        config.acc_schedule = Some(AccelerationSchedule::tvac());
+        let coefficients = |k: usize| {
+            let consts = config.policy_at(k);
+            ((consts.c1 * 1e12).round() / 1e12, (consts.c2 * 1e12).round() / 1e12)
+        };
+        assert_eq!(coefficients(1), (2.5, 0.5));
+        assert_eq!(coefficients(6), (1.5, 1.5));
+        assert_eq!(coefficients(11), (0.5, 2.5));
+    }
+
+    #[test]
+    fn converged_swarm_is_not_diverse() {
+        let position = vec![vec![1.0, 2.0], vec![1.002, 2.0], vec![0.998, 2.0], vec![1.0, 2.0]];
+        let spread = Spread::of(&position, &[1.0, 2.0], 0.01);
+        assert_eq!(spread.within_radius, 1.0);
+        assert!(!spread.diverse());
+        assert!((spread.std_dev[0] - 0.002 / 2f64.sqrt()).abs() < 1e-12);
+        assert_eq!(spread.std_dev[1], 0.0);
+        let position = vec![vec![1.0, 2.0], vec![3.0, 2.0], vec![1.0, -2.0], vec![1.0, 2.001]];
+        let spread = Spread::of(&position, &[1.0, 2.0], 0.01);
+        assert_eq!(spread.within_radius, 0.5);
+        assert!(!spread.diverse());
+        let spread = Spread::of(&position[1..], &[1.0, 2.0], 0.01);
+        assert!(spread.diverse());
+    }
+
+    #[test]
+    fn first_stopping_rule_ends_the_run() {
+        let target = |tol: f64| Target {
+            target: 0.0,
+            tol,
+            stag_eps: 0.0,
+            stag_window: usize::MAX,
+            smoothed: false,
+        };
+        let mut reached = config(10, 2, sphere);
+        reached.stop.iterations = Some(1000);
+        reached.stop.target = Some(target(1e-2));
+        let result = run(reached).unwrap();
+        assert!(matches!(result.stop_reason, StopReason::Target));
+        assert!(result.iterations < 1000 && result.best_value <= 1e-2);
+        let mut capped = config(10, 2, sphere);
+        capped.stop.iterations = Some(5);
+        capped.stop.target = Some(target(1e-300));
+        let result = run(capped).unwrap();
+        assert!(matches!(result.stop_reason, StopReason::Iterations));
+        assert_eq!(result.iterations, 5);
+    }
+
+    #[test]
+    fn unreachable_target_stops_on_stagnation() {
+        let mut config = config(10, 1, trap);
+        config.stop.iterations = None;
+        config.stop.target = Some(Target {
+            target: -1.0,
+            tol: 1e-9,
+            stag_eps: 1e-12,
+            stag_window: 50,
+            smoothed: false,
+        });
+        let result = run(config).unwrap();
+        assert!(matches!(result.stop_reason, StopReason::Stagnation));
+        assert!(result.best_value >= 0.0);
+    }
+
+    #[test]
+    fn initial_positions_lie_in_the_init_range() {
+        let mut config = config(50, 3, sphere);
+        config.init_range = Some((2.0, 3.0));
+        let optimizer = Optimizer::new(config).unwrap();
+        let x = optimizer.swarm().position.iter().flatten();
+        assert!(x.clone().all(|&xj| (2.0..=3.0).contains(&xj)));
+        assert!(x.clone().any(|&xj| xj < 2.5) && x.clone().any(|&xj| xj > 2.5));
+    }
+
+    #[test]
+    fn seeded_runs_are_reproducible() {
+        let first = run(config(8, 3, benchmarks::rastrigin)).unwrap();
+        let second = run(config(8, 3, benchmarks::rastrigin)).unwrap();
+        assert_eq!(first.best_position, second.best_position);
+        assert_eq!(first.best_value, second.best_value);
+    }
+
+    #[test]
+    fn steps_follow_the_same_path_as_a_run() {
+        let mut whole = config(8, 3, benchmarks::rastrigin);
+        whole.stop.iterations = Some(10);
+        let result = run(whole).unwrap();
+        let mut optimizer = Optimizer::new(config(8, 3, benchmarks::rastrigin)).unwrap();
+        for _ in 0..10 {
+            optimizer.step().unwrap();
+        }
+        assert_eq!(optimizer.iteration(), result.iterations);
+        assert_eq!(optimizer.best(), (&result.best_position[..], result.best_value));
+        assert_eq!(optimizer.swarm().position, result.final_position);
+        assert_eq!(optimizer.evaluations(), result.evaluations);
+    }
+
+    #[test]
+    fn mutation_helps_a_small_swarm_out_of_local_minima() {
+        let median = |mutation: f64| {
+            let mut mutated = config(5, 5, benchmarks::rastrigin);
+            mutated.stop.iterations = Some(1000);
+            mutated.policy.mutation = mutation;
+            mutated.policy.mutation_scale = 0.5;
+            Summary::of(&run_repeats(mutated, 20).unwrap(), 0.0, 1e-3).median
+        };
+        assert!(median(0.01) < median(0.0));
+    }
+
+    #[test]
+    fn best_ever_never_gets_worse() {
+        let mut optimizer = Optimizer::new(config(10, 2, benchmarks::ackley)).unwrap();
+        let mut best = optimizer.best().1;
+        for _ in 0..50 {
+            optimizer.step().unwrap();
+            let (_, value) = optimizer.best();
+            assert!(value <= best);
+            best = value;
+        }
+    }
+
+    #[test]
+    fn nan_everywhere_is_an_error() {
+        let f = expr::Expression::parse("sqrt(x - 10)").unwrap().into_fn();
+        let result = run(config(5, 1, f));
+        assert!(matches!(result, Err(RunError::AllNaN)));
+    }
+
+    #[test]
+    fn callback_ends_the_run() {
+        let mut config = config(4, 2, sphere);
+        let mut calls = 0;
+        config.on_iteration = Some(Box::new(move |_: &IterationStats| {
+            calls += 1;
+            if calls == 5 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        }));
+        let result = run(config).unwrap();
+        assert_eq!(result.iterations, 5);
+        assert!(matches!(result.stop_reason, StopReason::Callback));
+    }
+
+    #[test]
+    fn interrupt_ends_the_run_before_the_next_iteration() {
+        let mut interrupted = config(4, 2, sphere);
+        let flag = Arc::new(AtomicBool::new(false));
+        interrupted.interrupt = Some(Arc::clone(&flag));
+        let mut calls = 0;
+        interrupted.on_iteration = Some(Box::new(move |_: &IterationStats| {
+            calls += 1;
+            if calls == 7 {
+                flag.store(true, atomic::Ordering::Relaxed);
+            }
+            ControlFlow::Continue(())
+        }));
+        let result = run(interrupted).unwrap();
+        assert_eq!(result.iterations, 7);
+        assert!(matches!(result.stop_reason, StopReason::Interrupted));
+    }
+
+    #[test]
+    fn every_iteration_evaluates_every_particle_once() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&count);
+        let mut config = config(6, 2, move |x: &[f64]| {
+            counted.fetch_add(1, atomic::Ordering::Relaxed);
+            sphere(x)
+        });
+        config.stop.iterations = Some(10);
+        let result = run(config).unwrap();
+        assert_eq!(count.load(atomic::Ordering::Relaxed), 6 * (1 + 10));
+        assert_eq!(result.evaluations, 6 * (1 + 10));
+    }
+
+    #[test]
+    fn elitism_keeps_the_best_under_noise() {
+        let bests = |elitism: bool| {
+            let mut noisy = config(8, 2, sphere);
+            noisy.stop.iterations = Some(50);
+            noisy.noise = Some(0.5);
+            noisy.reeval_every = Some(1);
+            noisy.policy.elitism = elitism;
+            noisy.record_history = true;
+            let history = run(noisy).unwrap().history.unwrap();
+            history.iterations.iter().map(|stats| stats.global_best).collect::<Vec<f64>>()
+        };
+        let never_worse = |bests: &[f64]| bests.windows(2).all(|pair| pair[1] <= pair[0]);
+        assert!(never_worse(&bests(true)));
+        assert!(!never_worse(&bests(false)));
+    }
+
+    #[test]
+    fn evaluation_budget_is_never_overshot() {
+        for budget in [6, 11, 50, 53] {
+            let mut limited = config(6, 2, sphere);
+            limited.stop.iterations = None;
+            limited.stop.evaluations = Some(budget);
+            let result = run(limited).unwrap();
+            assert!(result.evaluations <= budget);
+            assert!(budget - result.evaluations < 6);
+            assert!(matches!(result.stop_reason, StopReason::EvaluationBudget));
+        }
+        let variants: [fn(&mut PsoConfig); 6] = [
+            |c| c.obl_init = true,
+            |c| c.grid_seed = Some(3),
+            |c| c.integer_dims = vec![0],
+            |c| (c.grid_seed, c.integer_dims, c.seed_corners) = (Some(3), vec![1], 2),
+            |c| c.reeval_every = Some(2),
+            |c| c.polish = Some(100),
+        ];
+        for (variant, budget) in variants.iter().flat_map(|v| [21, 50, 53].map(|b| (v, b))) {
+            let mut limited = config(6, 2, sphere);
+            limited.stop.iterations = None;
+            limited.stop.evaluations = Some(budget);
+            variant(&mut limited);
+            assert!(run(limited).unwrap().evaluations <= budget);
+        }
+        // Every position is evaluated once, or twice with the opposite of a random one
+        for (variant, initial) in variants[..4].iter().zip([12, 9 + 6, 6, 9 + 6]) {
+            let mut unmoved = config(6, 2, sphere);
+            unmoved.stop.iterations = Some(0);
+            variant(&mut unmoved);
+            assert_eq!(run(unmoved).unwrap().evaluations, initial);
+        }
+    }
+
+    #[test]
+    fn grid_cells_count_towards_the_budget() {
+        let mut seeded = config(4, 1, sphere);
+        seeded.stop.iterations = None;
+        seeded.grid_seed = Some(100);
+        seeded.stop.evaluations = Some(20);
+        assert!(matches!(run(seeded), Err(RunError::InvalidConfig(_))));
+        let mut seeded = config(4, 1, sphere);
+        seeded.stop.iterations = None;
+        seeded.grid_seed = Some(100);
+        seeded.stop.evaluations = Some(110);
+        assert!(run(seeded).unwrap().evaluations <= 110);
+    }
+
+    #[test]
+    fn grid_seed_starts_near_the_bottom_of_a_bowl() {
+        let bowl = |x: &[f64]| (x[0] - 2.0).powi(2);
+        let mut seeded = config(4, 1, bowl);
+        seeded.grid_seed = Some(20);
+        let mut r = StdRng::seed_from_u64(6);
+        let swarm: ParticleSwarm = initial_swarm(&seeded, &bowl, &mut r, 6);
+        // The four best cells of width 0.5 are the two on either side of the minimum
+        assert!(swarm.position.iter().all(|x| (x[0] - 2.0).abs() <= 1.0));
+    }
+
+    #[test]
+    fn resumed_run_continues_the_checkpointed_one() {
+        let path = std::env::temp_dir().join(format!("pso-test-{}.json", std::process::id()));
+        let mut uninterrupted = config(6, 2, benchmarks::rastrigin);
+        uninterrupted.stop.iterations = Some(20);
+        let whole = run(uninterrupted).unwrap();
+        let mut first = config(6, 2, benchmarks::rastrigin);
+        first.stop.iterations = Some(10);
+        first.checkpoint = Some(path.clone());
+        first.checkpoint_every = 10;
+        run(first).unwrap();
+        let checkpoint = Checkpoint::read(&path).unwrap();
+        let evaluations = checkpoint.evaluations;
+        assert_eq!(evaluations, 6 * (1 + 10));
+        let mut rest = config(6, 2, benchmarks::rastrigin);
+        rest.stop.iterations = Some(20);
+        rest.warm_start = Some(checkpoint);
+        let resumed = run(rest).unwrap();
+        assert_eq!(resumed.iterations, whole.iterations);
+        assert_eq!(resumed.best_position, whole.best_position);
+        assert_eq!(resumed.best_value, whole.best_value);
+        assert_eq!(resumed.evaluations, whole.evaluations);
+        let mut stepped = config(6, 2, benchmarks::rastrigin);
+        stepped.warm_start = Some(Checkpoint::read(&path).unwrap());
+        fs::remove_file(&path).unwrap();
+        let mut optimizer = Optimizer::new(stepped).unwrap();
+        assert_eq!(optimizer.evaluations(), evaluations);
+        optimizer.step().unwrap();
+        assert_eq!(optimizer.evaluations(), evaluations + 6);
+    }
+
+    #[test]
+    fn integer_coordinates_stay_integral() {
+        let mut config = config(8, 2, |x: &[f64]| (x[0] - 2.4).powi(2) + (x[1] - 0.3).powi(2));
+        config.integer_dims = vec![0];
+        let result = run(config).unwrap();
+        assert_eq!(result.best_position[0], 2.0);
+        assert!(result.final_position.iter().all(|x| x[0].fract() == 0.0));
+        assert!((result.best_position[1] - 0.3).abs() < 1e-3);
+    }
+
+    #[test]
+    fn penalty_holds_the_best_at_the_boundary() {
+        let mut config = config(10, 2, |x: &[f64]| (x[0] - 3.0).powi(2) + x[1] * x[1]);
+        config.stop.iterations = Some(200);
+        // The unconstrained minimum at x0 = 3 violates x0 <= 1
+        config.penalty = Some(Penalty {
+            constraints: vec![Box::new(|x: &[f64]| x[0] - 1.0)],
+            coeff: 1e6,
+        });
+        let result = run(config).unwrap();
+        assert!((result.best_position[0] - 1.0).abs() < 1e-2);
+        assert!((result.best_value - 4.0).abs() < 5e-2);
+    }
+
+    #[test]
+    fn listed_restart_seeds_reproduce_single_runs() {
+        let seeds = [11, 22, 33];
+        let mut restarts = config(6, 2, benchmarks::rastrigin);
+        (restarts.seeds, restarts.restarts) = (seeds.to_vec(), 3);
+        let kept = run(restarts).unwrap();
+        let single = |seed: u64| {
+            let mut config = config(6, 2, benchmarks::rastrigin);
+            config.seed = seed;
+            run(config).unwrap()
+        };
+        let singles = seeds.map(single);
+        let best = singles.iter().min_by(|a, b| a.best_value.total_cmp(&b.best_value)).unwrap();
+        assert_eq!(kept.seed, best.seed);
+        assert_eq!(kept.best_position, best.best_position);
+        assert_eq!(kept.best_value, best.best_value);
+    }
+
+    #[test]
+    fn failing_program_ends_the_run() {
+        let program = Arc::new(external::Program::new("read x; test $x = 0.5 && exit 1; echo $x"));
+        let p = Arc::clone(&program);
+        let mut config = config(2, 1, move |x: &[f64]| p.eval(x));
+        config.init = Some(vec![vec![0.25], vec![0.5]]);
+        config.program = Some(program);
+        match run(config) {
+            Err(RunError::ExternalObjective(reason)) => assert!(reason.contains("exited")),
+            _ => panic!("the run should have failed with the program"),
+        }
+    }
+
+    #[test]
+    fn f32_swarm_converges_to_f32_tolerance() {
+        let mut config = config(10, 2, sphere);
+        config.precision = Precision::F32;
+        config.stop.iterations = Some(300);
+        let result = run(config).unwrap();
+        assert!(result.best_value < 1e-6, "{}", result.best_value);
+        assert!(result.best_position.iter().all(|&x| (x as f32) as f64 == x));
+    }
+
+    #[test]
+    fn time_limit_stops_promptly() {
+        let mut config = config(2, 1, |x: &[f64]| {
+            std::thread::sleep(Duration::from_millis(1));
+            sphere(x)
+        });
+        config.stop.iterations = None;
+        config.stop.time = Some(Duration::from_millis(50));
+        let start = Instant::now();
+        let result = run(config).unwrap();
+        assert!(matches!(result.stop_reason, StopReason::TimeLimit));
+        // The clock is read every few iterations, each of a few milliseconds
+        assert!(start.elapsed() < Duration::from_millis(500), "{:?}", start.elapsed());
+    }
+
+    #[test]
+    fn collapse_short_of_the_target_is_reported() {
+        let mut config = config(10, 1, trap);
+        config.stop.iterations = Some(300);
+        config.stop.target = Some(Target {
+            target: -1.0,
+            tol: 1e-9,
+            stag_eps: 0.0,
+            stag_window: usize::MAX,
+            smoothed: false,
+        });
+        config.diameter_eps = Some(1e-3);
+        let result = run(config).unwrap();
+        assert!(result.collapsed_at.is_some_and(|k| k < 300));
+        assert!(result.best_value >= 0.0);
+    }
+
+    #[test]
+    fn pinned_coordinate_stays_exact() {
+        let mut config = config(8, 3, sphere);
+        config.fixed = vec![(1, 0.75)];
+        let result = run(config).unwrap();
+        assert_eq!(result.best_position[1], 0.75);
+        assert!(result.final_position.iter().all(|x| x[1] == 0.75));
+        assert!((result.best_value - 0.75 * 0.75).abs() < 1e-3);
+    }
+
+    #[test]
+    fn noise_of_zero_changes_nothing() {
+        let noiseless = run(config(6, 2, benchmarks::ackley)).unwrap();
+        let mut config = config(6, 2, benchmarks::ackley);
+        config.noise = Some(0.0);
+        let noisy = run(config).unwrap();
+        assert_eq!(noisy.best_position, noiseless.best_position);
+        assert_eq!(noisy.best_value, noiseless.best_value);
+    }
+
+    #[test]
+    fn sphere_result_is_close_to_the_known_optimum() {
+        let mut config = config(10, 2, benchmarks::sphere);
+        config.stop.iterations = Some(300);
+        let mut result = run(config).unwrap();
+        let (x, y) = benchmarks::known_optimum("sphere", 2).unwrap();
+        result.compare_to_optimum(&x, y);
+        assert!(result.value_gap.is_some_and(|gap| gap < 1e-9));
+        assert!(result.position_error.is_some_and(|error| error < 1e-4));
+    }
+
+    #[test]
+    fn csv_has_the_header_and_a_row_per_iteration() {
+        let path = std::env::temp_dir().join(format!("pso-test-{}.csv", std::process::id()));
+        let mut config = config(4, 2, sphere);
+        config.stop.iterations = Some(5);
+        config.csv = Some(Box::new(File::create(&path).unwrap()));
+        let result = run(config).unwrap();
+        let csv = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(lines.len(), 1 + 6);
+        for (i, line) in lines[1..].iter().enumerate() {
+            let columns = line.split(',').collect::<Vec<&str>>();
+            assert_eq!(columns.len(), 5);
+            assert_eq!(columns[0], i.to_string());
+            assert_eq!(columns[1].split(';').count(), 2);
+        }
+        let last = lines[6].split(',').collect::<Vec<&str>>();
+        assert_eq!(last[2].parse::<f64>().unwrap(), result.best_value);
+    }
+}
@@ -1,380 +1,2225 @@
+use clap::builder::styling::{AnsiColor, Effects, Styles};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
 use colored::Colorize;
-use rand;
+use pso::ascii;
+use pso::benchmarks::{self, Objective};
+use pso::expr;
+use pso::external::Program;
+use pso::init::InitMethod;
+use pso::{
+    run_each, run_policies, run_repeats, AccelerationSchedule, BoundMode, Bounds, Checkpoint,
+    History, LinearSchedule, ObjectiveFn, OptimizationPolicy, Penalty, Precision, PsoConfig,
+    RunResult, StopReason, StoppingRule, Summary, Target, Topology, UpdatePolicy, VelocityModel,
+};
+use serde::Deserialize;
+use std::error::Error;
 use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::level_filters::LevelFilter;
+use tracing::{debug, info, warn};
+
+/// Errors in red, as the run errors printed with `colored`
+const STYLES: Styles = Styles::styled()
+    .error(AnsiColor::Red.on_default().effects(Effects::BOLD))
+    .invalid(AnsiColor::Red.on_default())
+    .header(AnsiColor::Green.on_default().effects(Effects::BOLD))
+    .usage(AnsiColor::Green.on_default().effects(Effects::BOLD))
+    .literal(AnsiColor::Cyan.on_default().effects(Effects::BOLD))
+    .placeholder(AnsiColor::Cyan.on_default());
+
+/// Particle Swarm Optimization Demo
+#[derive(Parser)]
+#[command(styles = STYLES, args_override_self = true)]
+struct Args {
+    /// TOML file with default values for n, dimension, c1, c2, w, lower, upper, iterations,
+    /// threshold, seed and objective, overridden by flags
+    #[arg(long, value_name = "PATH")]
+    config: Option<String>,
+    /// Number of particles (required, here or in the configuration)
+    #[arg(short = 'n', value_parser = particles)]
+    n: Option<usize>,
+    /// Number of coordinates of each particle [default: 1]
+    #[arg(short = 'd', value_parser = dimension)]
+    d: Option<usize>,
+    /// Search for the maximum instead of the minimum
+    #[arg(long)]
+    maximize: bool,
+    /// Error threshold, same as --target 0 --target-tol <E>
+    #[arg(short = 'e', value_parser = threshold, conflicts_with_all = ["target", "target_tol"])]
+    e: Option<f64>,
+    /// Stop once the best value is this close to the target, required with --maximize unless -i
+    /// or --max-time is given [default: 0]
+    #[arg(long, value_parser = target, allow_hyphen_values = true)]
+    target: Option<f64>,
+    /// Distance from the target at which to stop [default: 0.0001]
+    #[arg(long, value_parser = threshold)]
+    target_tol: Option<f64>,
+    /// Smallest change of the best value counted as an improvement
+    #[arg(long, value_parser = stag_eps, default_value = "1e-12")]
+    stag_eps: f64,
+    /// Stop after this many iterations without improvement
+    #[arg(long, value_parser = stag_window, default_value = "50")]
+    stag_window: usize,
+    /// Average the global best over this many last iterations, logged every iteration at the debug
+    /// level
+    #[arg(long, value_name = "W", value_parser = ma_window)]
+    ma_window: Option<usize>,
+    /// Count improvements of the moving average of --ma-window towards --stag-window rather than of
+    /// the best value itself, so that noise in the best value does not end the run too soon
+    #[arg(long, requires = "ma_window")]
+    ma_stagnation: bool,
+    /// Maximum number of iterations, stopping earlier only at a target given with -e or --target
+    /// (uses the target if not provided)
+    #[arg(short = 'i', value_parser = iterations)]
+    i: Option<usize>,
+    /// Stop every run after this many seconds of wall-clock time, or whichever of -i and the
+    /// target comes first
+    #[arg(long, value_name = "SECS", value_parser = max_time)]
+    max_time: Option<Duration>,
+    /// Stop every run before an iteration that would take the objective evaluations past this
+    /// many, the initialization included, or whichever of -i, --max-time and the target comes first
+    #[arg(long, value_name = "EVALS", value_parser = budget)]
+    budget: Option<usize>,
+    /// Stop once the mean absolute velocity falls below this
+    #[arg(long, value_parser = vel_tol)]
+    vel_tol: Option<f64>,
+    /// Warn of premature convergence once the diagonal of the box around the swarm falls below
+    /// this short of the target
+    #[arg(long, value_parser = diameter)]
+    diameter_eps: Option<f64>,
+    /// Also draw every particle anew within the bounds whenever that happens, keeping the
+    /// personal bests
+    #[arg(long, requires = "diameter_eps")]
+    diameter_reinit: bool,
+    /// Distance from the best within which a particle counts as converged
+    #[arg(long, value_parser = conv_radius, default_value = "0.01")]
+    conv_radius: f64,
+    /// Objective function, one of sphere, quadratic, rastrigin, ackley, rosenbrock, griewank,
+    /// schwefel, or several separated by commas to optimize each in turn [default: quadratic]
+    #[arg(short = 'f', value_name = "NAME", value_delimiter = ',', value_parser = objective)]
+    f: Option<Vec<Objective>>,
+    /// Objective function as an expression in x, y, z or x0, x1, ... (overrides -f)
+    #[arg(long, value_parser = expression, allow_hyphen_values = true)]
+    expr: Option<expr::Expression>,
+    /// Several objective expressions separated by semicolons, optimized as their sum weighted by
+    /// --objective-weights (overrides -f)
+    #[arg(long, value_name = "EXPRS", value_delimiter = ';', value_parser = expression)]
+    #[arg(allow_hyphen_values = true, conflicts_with = "expr")]
+    multi_objective: Option<Vec<expr::Expression>>,
+    /// Weight of each expression of --multi-objective [default: 1 each]
+    #[arg(long, value_name = "W", value_delimiter = ',', value_parser = objective_weight)]
+    #[arg(allow_hyphen_values = true, requires = "multi_objective")]
+    objective_weights: Option<Vec<f64>>,
+    /// Shell command computing the objective, given the coordinates separated by spaces as a line
+    /// on stdin and printing the value on stdout; run once per distinct position (overrides -f)
+    #[arg(long, value_name = "PROGRAM", conflicts_with_all = ["expr", "multi_objective"])]
+    objective_from_cmd: Option<String>,
+    /// Inequality between two expressions, e.g. "x + y <= 1", penalized where violated instead of
+    /// enforced; may be repeated
+    #[arg(long, value_parser = constraint, allow_hyphen_values = true)]
+    constraint: Vec<expr::Constraint>,
+    /// Coefficient of the squared constraint violation added to the objective [default: 1000]
+    #[arg(long, value_parser = penalty, requires = "constraint")]
+    penalty: Option<f64>,
+    /// Standard deviation of Gaussian noise added to every evaluation of the objective, drawn from
+    /// the seed [default: 0]
+    #[arg(long, value_name = "SIGMA", value_parser = noise)]
+    objective_noise: Option<f64>,
+    /// Evaluate the personal bests again every this many iterations, so that a noisy objective
+    /// does not hold on to a lucky draw
+    #[arg(long, value_name = "K", value_parser = reeval_every)]
+    reeval_best: Option<usize>,
+    /// Never let the global best get worse: keep the best position ever seen as the attractor of
+    /// the swarm, and its value, when the personal bests are found worse on evaluating them again
+    #[arg(long, conflicts_with = "cpso")]
+    elitism: bool,
+    /// Refine the best position after the run with a coordinate search, within the bounds
+    #[arg(long)]
+    polish: bool,
+    /// Rounds of the coordinate search of --polish [default: 100]
+    #[arg(long, value_name = "K", value_parser = polish_iterations, requires = "polish")]
+    polish_iters: Option<usize>,
+    /// Optimum to report the distance of the best position and value from, one value per
+    /// coordinate [default: the minimizer of -f when minimizing]
+    #[arg(long, value_name = "X", value_delimiter = ',', value_parser = value)]
+    #[arg(allow_hyphen_values = true)]
+    known_optimum: Option<Vec<f64>>,
+    /// Inertia weight [default: 0.729]
+    #[arg(short = 'w', value_parser = inertia, allow_hyphen_values = true)]
+    w: Option<f64>,
+    /// Cognitive coefficient, pull towards the particle's own best [default: 0.5, 2.05 with
+    /// constriction]
+    #[arg(long, value_parser = coefficient)]
+    c1: Option<f64>,
+    /// Social coefficient, pull towards the best of the neighborhood [default: 0.5, 2.05 with
+    /// constriction]
+    #[arg(long, value_parser = coefficient)]
+    c2: Option<f64>,
+    /// Inertia weight changing over a fixed number of iterations, linear:<start>:<end>
+    #[arg(long, value_name = "SCHEDULE", value_parser = w_schedule)]
+    w_schedule: Option<LinearSchedule>,
+    /// c1 and c2 changing over the iterations given by -i, tvac (c1 2.5 to 0.5, c2 0.5 to 2.5)
+    #[arg(long, value_name = "SCHEDULE", value_parser = acc_schedule)]
+    acc_schedule: Option<AccelerationSchedule>,
+    /// Velocity update, inertia, constriction or fips (both need c1 + c2 > 4), or bare-bones to
+    /// sample positions around the personal and neighborhood best without velocities, or charged
+    /// for inertia with half of the particles repelling each other, or gcpso for inertia with the
+    /// best particle sampling a box around the global best that grows on improvement and shrinks
+    /// on failure [default: inertia]
+    #[arg(long, value_parser = model)]
+    model: Option<VelocityModel>,
+    /// Charge q of the charged particles, pushed apart by q / r^2 at distance r, with --model
+    /// charged [default: 1]
+    #[arg(long, value_name = "Q", value_parser = charge)]
+    charge: Option<f64>,
+    /// Draw every position anew within the bounds each iteration instead of moving particles, as
+    /// a baseline
+    #[arg(long, conflicts_with = "model")]
+    random_search: bool,
+    /// Run once with each of these velocity updates, named as for --model, from the same seed and
+    /// compare them in a table [default: inertia,constriction,bare-bones]
+    #[arg(long, value_name = "MODELS", value_delimiter = ',', value_parser = compared_model)]
+    #[arg(num_args = 0..=1, default_missing_value = "inertia,constriction,bare-bones")]
+    #[arg(conflicts_with_all = ["model", "random_search", "repeats", "plot", "ascii_plot"])]
+    compare_models: Option<Vec<(String, VelocityModel)>>,
+    /// Leave the pull towards the personal best out of the velocity update, for a social-only
+    /// swarm
+    #[arg(long)]
+    no_self_best: bool,
+    /// Leave the pull towards the neighborhood best out of the velocity update, for a
+    /// cognitive-only swarm
+    #[arg(long)]
+    no_global_best: bool,
+    /// Draw r1 and r2 of every odd particle as 1 - r of the particle before it, antithetic
+    /// variates for less variance between the pairs; n should be even for every particle to be
+    /// paired
+    #[arg(long)]
+    antithetic: bool,
+    /// Maximum absolute velocity per coordinate [default: unbounded]
+    #[arg(long, value_parser = vmax)]
+    vmax: Option<f64>,
+    /// Probability per particle and iteration of a random kick to its velocity
+    #[arg(long, value_name = "P", value_parser = mutation, default_value = "0")]
+    mutation: f64,
+    /// Standard deviation of the kick
+    #[arg(long, value_name = "S", value_parser = mutation_scale, default_value = "0.1")]
+    mutation_scale: f64,
+    /// Draw a particle anew within the bounds at rest, keeping its personal best, once that has
+    /// not improved for this many iterations
+    #[arg(long, value_name = "K", value_parser = reinit_after)]
+    reinit_after: Option<usize>,
+    /// Split the coordinates among this many sub-swarms of n particles each, evaluating every
+    /// slice together with the best slices of the other sub-swarms (cooperative PSO)
+    #[arg(long, value_name = "S", value_parser = sub_swarms)]
+    #[arg(conflicts_with_all = [
+        "init", "particles_from", "warm_start", "checkpoint", "csv", "history", "vel_tol",
+        "diameter_eps", "ma_window",
+    ])]
+    cpso: Option<usize>,
+    /// Weight of each particle in the choice of the global best, its value moved towards the better
+    /// end by that factor; one per particle [default: all equal]
+    #[arg(long, value_name = "W", value_delimiter = ',', value_parser = weight)]
+    weights: Option<Vec<f64>>,
+    /// Neighborhood of each particle, gbest, ring:<k> or vonneumann
+    #[arg(long, value_parser = topology, default_value = "gbest")]
+    topology: Topology,
+    /// Lower bound of the search space [default: unbounded]
+    #[arg(long, value_parser = bound, allow_hyphen_values = true)]
+    lower: Option<f64>,
+    /// Upper bound of the search space [default: unbounded]
+    #[arg(long, value_parser = bound, allow_hyphen_values = true)]
+    upper: Option<f64>,
+    /// One lo:hi interval per coordinate, separated by commas, each optionally followed by
+    /// :MODE in place of --bound-mode for its coordinate, e.g. 0:6.29:wrap (replaces --lower and
+    /// --upper)
+    #[arg(long, value_name = "LO:HI[:MODE]", value_delimiter = ',', value_parser = interval)]
+    #[arg(allow_hyphen_values = true)]
+    bounds: Option<Vec<(f64, f64, Option<BoundMode>)>>,
+    /// How to handle particles leaving the bounds, clamp, reflect, or wrap for periodic
+    /// coordinates, which come back in through the other end with the same velocity
+    #[arg(long, value_name = "MODE", value_parser = bound_mode, default_value = "clamp")]
+    bound_mode: BoundMode,
+    /// Coordinates, counted from 0, whose positions are rounded to integers
+    #[arg(long, value_name = "J", value_delimiter = ',', value_parser = integer_dim)]
+    integer_dims: Vec<usize>,
+    /// Coordinates, counted from 0, held at a value throughout, as j=x separated by commas, e.g.
+    /// 1=3.5,3=0
+    #[arg(long, value_name = "J=X", value_delimiter = ',', value_parser = fixed)]
+    #[arg(allow_hyphen_values = true)]
+    fix: Vec<(usize, f64)>,
+    /// Floating-point type of positions and velocities, f64 or f32 for half the memory
+    #[arg(long, value_parser = precision, default_value = "f64")]
+    precision: Precision,
+    /// Verbose mode, logging every iteration and the whole swarm, same as --log-level trace
+    #[arg(short = 'v')]
+    verbose: bool,
+    /// Detail of the swarm logged by -v, 1 for the range of the positions and the mean speed per
+    /// coordinate or 2 for every position and velocity; implies -v [default: 1]
+    #[arg(long, value_name = "LEVEL", value_parser = verbose_level, conflicts_with = "quiet")]
+    verbose_level: Option<u8>,
+    /// Print only the result, logging only warnings and errors
+    #[arg(long, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Most detailed messages logged to stderr, off, error, warn, info, debug for every iteration
+    /// or trace for the whole swarm [default: info, trace with -v, warn with --quiet, off with
+    /// --events]
+    #[arg(long, value_name = "LEVEL", value_parser = log_level)]
+    log_level: Option<LevelFilter>,
+    /// Print the resolved configuration without running
+    #[arg(long)]
+    dry_run: bool,
+    /// Show a progress bar, with -i and text output only
+    #[arg(long)]
+    progress: bool,
+    /// Stream one JSON object per line to stderr: init, every iteration and done, in place of the
+    /// progress bar and of the log unless --log-level is given
+    #[arg(long)]
+    events: bool,
+    /// Write the trajectory of the global best to a CSV file
+    #[arg(long, value_name = "PATH")]
+    csv: Option<String>,
+    /// Write per-iteration diagnostics of the kept run to a JSON file
+    #[arg(long, value_name = "PATH")]
+    history: Option<String>,
+    /// Write the best position to a file, one coordinate per line and nothing else, the best of
+    /// all runs with --repeats
+    #[arg(long, value_name = "PATH")]
+    solution_out: Option<String>,
+    /// Draw the global best value per iteration to an SVG file, needs the plotters feature
+    #[arg(long, value_name = "PATH")]
+    plot: Option<String>,
+    /// Draw the final positions of a two-dimensional swarm instead
+    #[arg(long, requires = "plot")]
+    plot_positions: bool,
+    /// Print the objective over the bounds with the final swarm on top, a line chart for d = 1
+    /// and a heatmap for d = 2, with text output
+    #[arg(long, conflicts_with = "quiet")]
+    ascii_plot: bool,
+    /// Points sampled along each coordinate by --ascii-plot
+    #[arg(long, value_name = "COLS", value_parser = plot_cols, default_value = "60")]
+    #[arg(requires = "ascii_plot")]
+    plot_cols: usize,
+    /// Format of the result, text, json, or kv for sorted key=value lines with fixed number
+    /// formatting that are the same byte for byte for the same seed
+    #[arg(long, value_parser = format, default_value = "text")]
+    format: OutputFormat,
+    /// Digits after the decimal point of the values and positions in the text output and the
+    /// swarm logged by -v [default: as many as it takes to read them back exactly]
+    #[arg(long, value_name = "N", value_parser = digits)]
+    precision_digits: Option<usize>,
+    /// Use a fixed seed for random number generation, or a comma-separated list seeding each
+    /// repeat, or else each restart, in order; runs past the end of the list derive their seed
+    /// from the first as with a single seed
+    #[arg(long, value_delimiter = ',', value_parser = seed)]
+    seed: Option<Vec<u64>>,
+    /// Number of independent runs, reporting the best
+    #[arg(long, value_name = "K", value_parser = restarts, default_value = "1")]
+    restarts: usize,
+    /// Number of runs from consecutive seeds, reporting each and their statistics
+    #[arg(long, value_name = "K", value_parser = repeats, default_value = "1")]
+    repeats: usize,
+    /// Draw all restarts, repeats and functions from one random stream started from the seed,
+    /// each continuing where the previous run left it, rather than each from a seed derived from
+    /// the first; the runs can then only be reproduced together
+    #[arg(long)]
+    resume_rng: bool,
+    /// Initial positions of particles, n*d values with the coordinates of each particle in turn
+    #[arg(long, value_name = "X", value_delimiter = ',', value_parser = value)]
+    #[arg(allow_hyphen_values = true)]
+    init: Option<Vec<f64>>,
+    /// Range of random initial positions [default: the bounds, or -1:1 without bounds]
+    #[arg(long, value_name = "LO:HI", value_parser = range, allow_hyphen_values = true)]
+    init_range: Option<(f64, f64)>,
+    /// How random initial positions are spread, uniform, halton for the low-discrepancy Halton
+    /// sequence or lhs for Latin hypercube sampling
+    #[arg(long, value_name = "METHOD", value_parser = init_method, default_value = "uniform")]
+    init_method: InitMethod,
+    /// Start this many particles, at most n, at the corners of the range of random initial
+    /// positions, cycling through the corners, and draw the rest as usual
+    #[arg(long, value_name = "K", value_parser = corner_count)]
+    #[arg(conflicts_with_all = ["init", "particles_from", "warm_start", "cpso"])]
+    seed_corners: Option<usize>,
+    /// Evaluate the opposite lo + hi - x of every random initial position across the range of
+    /// initial positions, and start from whichever of the two is better
+    #[arg(long, conflicts_with_all = ["init", "particles_from", "warm_start", "cpso"])]
+    obl_init: bool,
+    /// Evaluate a grid of this many cells along each coordinate of the range of random initial
+    /// positions, and start the particles within the best cells, drawing the rest as usual
+    #[arg(long, value_name = "POINTS", value_parser = grid_points)]
+    #[arg(conflicts_with_all = ["init", "particles_from", "warm_start", "cpso", "obl_init"])]
+    #[arg(conflicts_with = "seed_corners")]
+    grid_seed: Option<usize>,
+    /// Initial velocities of particles, laid out like --init
+    #[arg(long, value_name = "V", value_delimiter = ',', value_parser = value)]
+    #[arg(allow_hyphen_values = true)]
+    vinit: Option<Vec<f64>>,
+    /// Range of random initial velocities [default: -1:1]
+    #[arg(long, value_name = "LO:HI", value_parser = range, allow_hyphen_values = true)]
+    vinit_range: Option<(f64, f64)>,
+    /// CSV file with one particle per row, d position columns then d velocity columns (replaces
+    /// --init and --vinit)
+    #[arg(long, value_name = "PATH")]
+    particles_from: Option<String>,
+    /// Save the state of the run to a JSON file every --checkpoint-every iterations
+    #[arg(long, value_name = "PATH")]
+    checkpoint: Option<String>,
+    /// Iterations between checkpoints
+    #[arg(long, value_name = "K", value_parser = checkpoint_every, default_value = "100")]
+    checkpoint_every: usize,
+    /// Continue the run saved in a checkpoint (replaces the initialization)
+    #[arg(long, value_name = "PATH")]
+    warm_start: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+enum OutputFormat {
+    Text,
+    Json,
+    Kv,
+}
 
 #[derive(Debug)]
-struct ParticleSwarm {
-    position: Vec<f64>,
-    velocity: Vec<f64>,
-    local_optimum: Vec<f64>,
-    global_optimum: Option<f64>,
+enum ParseError {
+    MissingArgument(String),
+    InvalidParticleNumber(String),
+    InvalidDimension(String),
+    InvalidIterations(String),
+    InvalidMaxTime(String),
+    InvalidBudget(String),
+    InvalidThreshold(String),
+    InvalidTarget(String),
+    InvalidStagnation(String),
+    InvalidWindow(String),
+    InvalidVelocityTolerance(String),
+    InvalidDiameter(String),
+    InvalidConvergenceRadius(String),
+    InvalidObjective(String),
+    InvalidExpression(String),
+    InvalidObjectiveWeight(String),
+    ObjectiveWeightCountMismatch { expected: usize, got: usize },
+    InvalidConstraint(String),
+    InvalidPenalty(String),
+    InvalidNoise(String),
+    InvalidReevaluation(String),
+    InvalidPolishIterations(String),
+    InvalidInertia(String),
+    InvalidCoefficient(String),
+    InvalidSchedule(String),
+    InvalidModel(String),
+    InvalidConstriction(String),
+    InvalidVelocityLimit(String),
+    InvalidMutation(String),
+    InvalidReinit(String),
+    InvalidCharge(String),
+    InvalidSubSwarms(String),
+    TooManySubSwarms { d: usize, s: usize },
+    InvalidWeight(String),
+    WeightCountMismatch { n: usize, got: usize },
+    InvalidTopology(String),
+    InvalidBounds(String),
+    InvalidBoundMode(String),
+    BoundsShapeMismatch { d: usize, got: usize },
+    InvalidIntegerDims(String),
+    InvalidFix(String),
+    InvalidPrecision(String),
+    InvalidRange(String),
+    InvalidInitMethod(String),
+    InvalidCornerCount(String),
+    InvalidGridPoints(String),
+    InvalidFormat(String),
+    InvalidLogLevel(String),
+    InvalidVerboseLevel(String),
+    InvalidSeed(String),
+    TooManySeeds { runs: usize, got: usize },
+    InvalidRestarts(String),
+    InvalidRepeats(String),
+    InvalidArgument(String),
+    FeatureDisabled { flag: String, feature: String },
+    PlotDimension(usize),
+    AsciiPlotDimension(usize),
+    InvalidPlotColumns(String),
+    InvalidDigits(String),
+    ConflictingArguments(String, String),
+    ConfigError { path: String, message: String },
+    ParticleFileError { path: String, message: String },
+    CheckpointError { path: String, message: String },
+    InvalidCheckpointInterval(String),
+    InitShapeMismatch { n: usize, d: usize, got: usize },
+    VInitLengthMismatch { expected: usize, got: usize },
+    OptimumLengthMismatch { d: usize, got: usize },
 }
 
-impl ParticleSwarm {
-    fn new(n: usize, x: Vec<f64>, v: Vec<f64>, f: fn(f64) -> f64, opt: &OptimizationPolicy) -> Self {
-        assert!(x.len() == n, "Position vector must have length equal to number of particles");
-        assert!(v.len() == n, "Velocity vector must have length equal to number of particles");
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingArgument(arg) => write!(f, "Missing argument for {}", arg),
+            ParseError::InvalidParticleNumber(arg) => {
+                write!(f, "Invalid number of particles, expected at least 1: {}", arg)
+            }
+            ParseError::InvalidDimension(arg) => write!(f, "Invalid dimension: {}", arg),
+            ParseError::InvalidIterations(arg) => {
+                write!(f, "Invalid number of iterations: {}", arg)
+            }
+            ParseError::InvalidMaxTime(arg) => {
+                write!(f, "Invalid time limit, expected a positive number of seconds: {}", arg)
+            }
+            ParseError::InvalidBudget(arg) => {
+                write!(f, "Invalid evaluation budget, expected at least 1: {}", arg)
+            }
+            ParseError::InvalidThreshold(arg) => {
+                write!(f, "Invalid error threshold, expected a finite number >= 0: {}", arg)
+            }
+            ParseError::InvalidTarget(arg) => {
+                write!(f, "Invalid target, expected a finite number: {}", arg)
+            }
+            ParseError::InvalidStagnation(arg) => {
+                write!(f, "Invalid stagnation criterion: {}", arg)
+            }
+            ParseError::InvalidWindow(arg) => {
+                write!(f, "Invalid moving average window, expected at least 1: {}", arg)
+            }
+            ParseError::InvalidVelocityTolerance(arg) => {
+                write!(f, "Invalid velocity tolerance: {}", arg)
+            }
+            ParseError::InvalidDiameter(arg) => {
+                write!(f, "Invalid diameter threshold, expected a positive number: {}", arg)
+            }
+            ParseError::InvalidConvergenceRadius(arg) => {
+                write!(f, "Invalid convergence radius: {}", arg)
+            }
+            ParseError::InvalidObjective(arg) => write!(f, "Unknown objective function: {}", arg),
+            ParseError::InvalidExpression(token) => write!(f, "Invalid expression at: {}", token),
+            ParseError::InvalidObjectiveWeight(arg) => {
+                write!(f, "Invalid objective weight, expected a finite number: {}", arg)
+            }
+            ParseError::ObjectiveWeightCountMismatch { expected, got } => write!(
+                f,
+                "--objective-weights needs one weight per objective of --multi-objective: expected {}, got {}",
+                expected,
+                got,
+            ),
+            ParseError::InvalidConstraint(arg) => {
+                write!(f, "Invalid constraint, expected <lhs> <= <rhs> or <lhs> >= <rhs>: {}", arg)
+            }
+            ParseError::InvalidPenalty(arg) => {
+                write!(f, "Invalid penalty coefficient, expected a finite number > 0: {}", arg)
+            }
+            ParseError::InvalidNoise(arg) => {
+                write!(f, "Invalid noise, expected a finite standard deviation >= 0: {}", arg)
+            }
+            ParseError::InvalidReevaluation(arg) => {
+                write!(f, "Invalid re-evaluation interval, expected at least 1: {}", arg)
+            }
+            ParseError::InvalidPolishIterations(arg) => {
+                write!(f, "Invalid number of polish rounds, expected at least 1: {}", arg)
+            }
+            ParseError::InvalidInertia(arg) => {
+                write!(f, "Invalid inertia weight, expected a finite number: {}", arg)
+            }
+            ParseError::InvalidCoefficient(arg) => {
+                write!(f, "Invalid acceleration coefficient, expected a finite number >= 0: {}", arg)
+            }
+            ParseError::InvalidSchedule(arg) => write!(f, "Invalid schedule: {}", arg),
+            ParseError::InvalidModel(arg) => write!(f, "Invalid velocity model: {}", arg),
+            ParseError::InvalidConstriction(phi) => {
+                write!(f, "Constriction requires c1 + c2 > 4, but c1 + c2 = {}", phi)
+            }
+            ParseError::InvalidVelocityLimit(arg) => {
+                write!(f, "Invalid velocity limit, expected a finite number > 0: {}", arg)
+            }
+            ParseError::InvalidMutation(arg) => write!(f, "Invalid mutation: {}", arg),
+            ParseError::InvalidCharge(arg) => {
+                write!(f, "Invalid charge, expected a finite number > 0: {}", arg)
+            }
+            ParseError::InvalidReinit(arg) => {
+                write!(f, "Invalid reinitialization interval, expected at least 1: {}", arg)
+            }
+            ParseError::InvalidSubSwarms(arg) => {
+                write!(f, "Invalid number of sub-swarms, expected at least 1: {}", arg)
+            }
+            ParseError::TooManySubSwarms { d, s } => {
+                write!(f, "--cpso needs at least one coordinate per sub-swarm: d = {}, got {}", d, s)
+            }
+            ParseError::InvalidWeight(arg) => {
+                write!(f, "Invalid weight, expected a finite number > 0: {}", arg)
+            }
+            ParseError::WeightCountMismatch { n, got } => {
+                write!(f, "--weights needs one weight per particle: expected {}, got {}", n, got)
+            }
+            ParseError::InvalidTopology(arg) => write!(f, "Invalid topology: {}", arg),
+            ParseError::InvalidBounds(arg) => write!(f, "Invalid search bounds: {}", arg),
+            ParseError::InvalidBoundMode(arg) => write!(f, "Invalid bound mode: {}", arg),
+            ParseError::InvalidIntegerDims(arg) => {
+                write!(f, "Invalid integer coordinate, expected 0 to d - 1: {}", arg)
+            }
+            ParseError::InvalidFix(arg) => {
+                let expected = "<j>=<x> with every j from 0 to d - 1 at most once";
+                write!(f, "Invalid fixed coordinate, expected {}: {}", expected, arg)
+            }
+            ParseError::InvalidPrecision(arg) => {
+                write!(f, "Invalid precision, expected f64 or f32: {}", arg)
+            }
+            ParseError::BoundsShapeMismatch { d, got } => {
+                write!(f, "--bounds needs one interval per coordinate: expected {}, got {}", d, got)
+            }
+            ParseError::InvalidRange(arg) => write!(f, "Invalid range, expected <lo:hi>: {}", arg),
+            ParseError::InvalidInitMethod(arg) => write!(f, "Invalid initialization method: {}", arg),
+            ParseError::InvalidGridPoints(arg) => {
+                write!(f, "Invalid number of grid cells, expected at least 1: {}", arg)
+            }
+            ParseError::InvalidCornerCount(arg) => {
+                write!(f, "Invalid number of corner particles, expected at least 1: {}", arg)
+            }
+            ParseError::InvalidFormat(arg) => write!(f, "Invalid output format: {}", arg),
+            ParseError::InvalidLogLevel(arg) => write!(f, "Invalid log level: {}", arg),
+            ParseError::InvalidVerboseLevel(arg) => {
+                write!(f, "Invalid verbose level, expected 1 or 2: {}", arg)
+            }
+            ParseError::InvalidSeed(arg) => write!(f, "Invalid seed: {}", arg),
+            ParseError::TooManySeeds { runs, got } => {
+                write!(f, "--seed lists {} seeds, more than the number of runs {}", got, runs)
+            }
+            ParseError::InvalidRestarts(arg) => write!(f, "Invalid number of restarts: {}", arg),
+            ParseError::InvalidRepeats(arg) => write!(f, "Invalid number of repeats: {}", arg),
+            ParseError::InvalidArgument(arg) => write!(f, "Unexpected argument: {}", arg),
+            ParseError::FeatureDisabled { flag, feature } => {
+                write!(f, "{} needs a build with the {} feature", flag, feature)
+            }
+            ParseError::PlotDimension(d) => {
+                write!(f, "--plot-positions needs a two-dimensional problem, got d = {}", d)
+            }
+            ParseError::AsciiPlotDimension(d) => {
+                write!(f, "--ascii-plot needs a one- or two-dimensional problem, got d = {}", d)
+            }
+            ParseError::InvalidPlotColumns(arg) => {
+                write!(f, "Invalid number of plot columns, expected at least 2: {}", arg)
+            }
+            ParseError::InvalidDigits(arg) => write!(f, "Invalid number of digits: {}", arg),
+            ParseError::ConflictingArguments(a, b) => {
+                write!(f, "{} and {} cannot be used together", a, b)
+            }
+            ParseError::ConfigError { path, message } => {
+                write!(f, "Could not read configuration {}: {}", path, message)
+            }
+            ParseError::ParticleFileError { path, message } => {
+                write!(f, "Could not read particles from {}: {}", path, message)
+            }
+            ParseError::CheckpointError { path, message } => {
+                write!(f, "Could not read checkpoint {}: {}", path, message)
+            }
+            ParseError::InvalidCheckpointInterval(arg) => {
+                write!(f, "Invalid checkpoint interval: {}", arg)
+            }
+            ParseError::InitShapeMismatch { n, d, got } => write!(
+                f,
+                "--init needs n*d = {}*{} = {} values, the {} coordinates of the first particle followed by those of the next: got {}",
+                n,
+                d,
+                n * d,
+                d,
+                got,
+            ),
+            ParseError::VInitLengthMismatch { expected, got } => write!(
+                f,
+                "--vinit needs one value per coordinate of every particle: expected {}, got {}",
+                expected,
+                got,
+            ),
+            ParseError::OptimumLengthMismatch { d, got } => write!(
+                f,
+                "--known-optimum needs one value per coordinate: expected {}, got {}",
+                d,
+                got,
+            ),
+        }
+    }
+}
 
-        let mut local_optimum = Vec::new();
-        for i in 0..n {
-            local_optimum.push(x[i]);
+impl Error for ParseError {}
+
+impl ParseError {
+    /// How clap labels the error when printing it
+    fn kind(&self) -> ErrorKind {
+        match self {
+            ParseError::MissingArgument(_) => ErrorKind::MissingRequiredArgument,
+            ParseError::ConflictingArguments(..) => ErrorKind::ArgumentConflict,
+            ParseError::ConfigError { .. }
+            | ParseError::ParticleFileError { .. }
+            | ParseError::CheckpointError { .. } => ErrorKind::Io,
+            _ => ErrorKind::ValueValidation,
         }
+    }
+}
 
-        let global_optimum = local_optimum
-            .iter()
-            .max_by(|&x, &y| match opt {
-                OptimizationPolicy::FindMinimum => f(*x).partial_cmp(&f(*y)).unwrap(),
-                OptimizationPolicy::FindMaximum => f(*y).partial_cmp(&f(*x)).unwrap(),
-            })
-            .unwrap();
-
-        Self {
-            position: x,
-            velocity: v,
-            global_optimum: Some(*global_optimum),
-            local_optimum,
-        }
-    }
-    fn new_random<R: rand::Rng>(
-        n: usize,
-        f: fn(f64) -> f64,
-        opt: &OptimizationPolicy,
-        r: &mut R,
-    ) -> Self {
-        let mut position = Vec::new();
-        let mut velocity = Vec::new();
-        let mut local_optimum = Vec::new();
-
-        for _ in 0..n {
-            let x: f64 = r.gen();
-            let v: f64 = r.gen();
-            position.push(x);
-            velocity.push(v);
-            local_optimum.push(x);
-        }
-
-        let global_optimum = local_optimum
-            .iter()
-            .max_by(|&x, &y| match opt {
-                OptimizationPolicy::FindMinimum => f(*x).partial_cmp(&f(*y)).unwrap(),
-                OptimizationPolicy::FindMaximum => f(*y).partial_cmp(&f(*x)).unwrap(),
-            })
-            .unwrap();
+struct RunOptions {
+    n: usize,
+    d: usize,
+    iter: Option<usize>,
+    max_time: Option<Duration>,
+    budget: Option<usize>,
+    maximize: bool,
+    target: Option<f64>,
+    target_tol: f64,
+    stag_eps: f64,
+    stag_window: usize,
+    ma_window: Option<usize>,
+    ma_stagnation: bool,
+    vel_tol: Option<f64>,
+    diameter_eps: Option<f64>,
+    diameter_reinit: bool,
+    conv_radius: f64,
+    objectives: Vec<Objective>,
+    known_optimum: Option<Vec<f64>>,
+    expr: Option<expr::Expression>,
+    /// The weighted parts of --multi-objective, whose sum is `expr`
+    objective_parts: Vec<(f64, expr::Expression)>,
+    objective_cmd: Option<String>,
+    constraints: Vec<expr::Constraint>,
+    penalty: f64,
+    noise: Option<f64>,
+    reeval_best: Option<usize>,
+    elitism: bool,
+    polish: Option<usize>,
+    w: f64,
+    w_schedule: Option<LinearSchedule>,
+    acc_schedule: Option<AccelerationSchedule>,
+    c1: f64,
+    c2: f64,
+    model: VelocityModel,
+    /// The models of --compare-models with their names and their c1 and c2
+    compared_models: Vec<(String, VelocityModel, f64, f64)>,
+    vmax: Option<f64>,
+    mutation: f64,
+    mutation_scale: f64,
+    reinit_after: Option<usize>,
+    charge: f64,
+    no_self_best: bool,
+    no_global_best: bool,
+    antithetic: bool,
+    cpso: Option<usize>,
+    weights: Option<Vec<f64>>,
+    topology: Topology,
+    bounds: Option<Bounds>,
+    integer_dims: Vec<usize>,
+    fixed: Vec<(usize, f64)>,
+    precision: Precision,
+    quiet: bool,
+    log_level: LevelFilter,
+    dump_swarm: bool,
+    progress: bool,
+    events: bool,
+    dry_run: bool,
+    csv: Option<String>,
+    history: Option<String>,
+    solution_out: Option<String>,
+    plot: Option<String>,
+    plot_positions: bool,
+    ascii_plot: bool,
+    plot_cols: usize,
+    init: Option<Vec<Vec<f64>>>,
+    vinit: Option<Vec<Vec<f64>>>,
+    init_range: Option<(f64, f64)>,
+    init_method: InitMethod,
+    seed_corners: usize,
+    obl_init: bool,
+    grid_seed: Option<usize>,
+    vinit_range: Option<(f64, f64)>,
+    /// Seeds of the first runs, a single one unless given as a list
+    seeds: Option<Vec<u64>>,
+    restarts: usize,
+    repeats: usize,
+    resume_rng: bool,
+    checkpoint: Option<String>,
+    checkpoint_every: usize,
+    warm_start: Option<Checkpoint>,
+    format: OutputFormat,
+    digits: Option<usize>,
+}
+
+/// Settings read by `--config`, each taking the place of the default of the matching flag
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    n: Option<usize>,
+    dimension: Option<usize>,
+    c1: Option<f64>,
+    c2: Option<f64>,
+    w: Option<f64>,
+    lower: Option<f64>,
+    upper: Option<f64>,
+    iterations: Option<usize>,
+    /// Same as `-e`
+    threshold: Option<f64>,
+    seed: Option<u64>,
+    objective: Option<String>,
+}
+
+fn read_config(path: &str) -> Result<FileConfig, ParseError> {
+    let error = |message: String| ParseError::ConfigError {
+        path: path.to_string(),
+        message,
+    };
+    let text = std::fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+    toml::from_str(&text).map_err(|e| error(e.to_string()))
+}
 
-        Self {
-            position,
-            velocity,
-            global_optimum: Some(*global_optimum),
-            local_optimum,
+/// One vector per particle
+type Rows = Vec<Vec<f64>>;
+
+/// Reads the positions and velocities of `n` particles of dimension `d` from a headerless CSV
+fn read_particles(path: &str, n: usize, d: usize) -> Result<(Rows, Rows), ParseError> {
+    let error = |message: String| ParseError::ParticleFileError {
+        path: path.to_string(),
+        message,
+    };
+    let text = std::fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+    let rows = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<&str>>();
+    if rows.len() != n {
+        return Err(error(format!(
+            "expected {} rows, one per particle, got {}",
+            n,
+            rows.len()
+        )));
+    }
+
+    let (mut x, mut v) = (Vec::with_capacity(n), Vec::with_capacity(n));
+    for (i, row) in rows.iter().enumerate() {
+        let values = row
+            .split(',')
+            .map(|c| {
+                c.trim()
+                    .parse::<f64>()
+                    .map_err(|_| error(format!("row {}: invalid value {}", i + 1, c)))
+            })
+            .collect::<Result<Vec<f64>, ParseError>>()?;
+        if values.len() != 2 * d {
+            return Err(error(format!(
+                "row {}: expected 2*d = {} columns, {} positions then {} velocities, got {}",
+                i + 1,
+                2 * d,
+                d,
+                d,
+                values.len()
+            )));
         }
+        x.push(values[..d].to_vec());
+        v.push(values[d..].to_vec());
     }
+    Ok((x, v))
 }
 
-impl fmt::Display for ParticleSwarm {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-"Positions: {:?}
-Velocities: {:?}",
-            self.position, self.velocity
-        )
+/// Default c1 and c2 of `model`
+fn default_coefficient(model: VelocityModel) -> f64 {
+    match model {
+        VelocityModel::Inertia
+        | VelocityModel::RandomSearch
+        | VelocityModel::BareBones
+        | VelocityModel::Charged
+        | VelocityModel::Gcpso => 0.5,
+        VelocityModel::Constriction | VelocityModel::Fips => 2.05,
     }
 }
 
-struct UpdatePolicy {
-    c1: f64,
-    c2: f64,
+/// Whether `model` scales its update by the constriction coefficient, which needs c1 + c2 > 4
+fn is_constricted(model: VelocityModel) -> bool {
+    matches!(model, VelocityModel::Constriction | VelocityModel::Fips)
+}
+
+/// Parses comma-separated benchmark names
+fn objective_list(names: &str) -> Result<Vec<Objective>, ParseError> {
+    names
+        .split(',')
+        .map(|name| Objective::from_name(name).ok_or(ParseError::InvalidObjective(name.to_string())))
+        .collect()
+}
+
+/// Parses `s` into a number accepted by `valid`, or reports it with `invalid`
+fn number<T: FromStr>(
+    s: &str,
+    valid: impl Fn(&T) -> bool,
+    invalid: fn(String) -> ParseError,
+) -> Result<T, ParseError> {
+    s.parse::<T>().ok().filter(valid).ok_or_else(|| invalid(s.to_string()))
+}
+
+fn particles(s: &str) -> Result<usize, ParseError> {
+    number(s, |n| *n > 0, ParseError::InvalidParticleNumber)
+}
+
+fn dimension(s: &str) -> Result<usize, ParseError> {
+    number(s, |d| *d > 0, ParseError::InvalidDimension)
+}
+
+fn iterations(s: &str) -> Result<usize, ParseError> {
+    number(s, |_| true, ParseError::InvalidIterations)
+}
+
+fn threshold(s: &str) -> Result<f64, ParseError> {
+    number(s, |e: &f64| e.is_finite() && *e >= 0.0, ParseError::InvalidThreshold)
+}
+
+fn target(s: &str) -> Result<f64, ParseError> {
+    number(s, |t: &f64| t.is_finite(), ParseError::InvalidTarget)
+}
+
+fn stag_eps(s: &str) -> Result<f64, ParseError> {
+    number(s, |e: &f64| e.is_finite() && *e >= 0.0, ParseError::InvalidStagnation)
 }
 
-impl UpdatePolicy {
-    fn new(c1: f64, c2: f64) -> Self {
-        Self { c1, c2 }
+fn stag_window(s: &str) -> Result<usize, ParseError> {
+    number(s, |k| *k > 0, ParseError::InvalidStagnation)
+}
+
+fn ma_window(s: &str) -> Result<usize, ParseError> {
+    number(s, |w| *w > 0, ParseError::InvalidWindow)
+}
+
+fn max_time(s: &str) -> Result<Duration, ParseError> {
+    let secs = number(s, |t: &f64| t.is_finite() && *t > 0.0, ParseError::InvalidMaxTime)?;
+    Ok(Duration::from_secs_f64(secs))
+}
+
+fn budget(s: &str) -> Result<usize, ParseError> {
+    number(s, |k| *k > 0, ParseError::InvalidBudget)
+}
+
+fn vel_tol(s: &str) -> Result<f64, ParseError> {
+    number(s, |e: &f64| e.is_finite() && *e > 0.0, ParseError::InvalidVelocityTolerance)
+}
+
+fn diameter(s: &str) -> Result<f64, ParseError> {
+    number(s, |e: &f64| e.is_finite() && *e > 0.0, ParseError::InvalidDiameter)
+}
+
+fn conv_radius(s: &str) -> Result<f64, ParseError> {
+    number(s, |r: &f64| r.is_finite() && *r > 0.0, ParseError::InvalidConvergenceRadius)
+}
+
+fn objective(s: &str) -> Result<Objective, ParseError> {
+    Objective::from_name(s).ok_or(ParseError::InvalidObjective(s.to_string()))
+}
+
+fn expression(s: &str) -> Result<expr::Expression, ParseError> {
+    expr::Expression::parse(s).map_err(|e| ParseError::InvalidExpression(e.token))
+}
+
+fn objective_weight(s: &str) -> Result<f64, ParseError> {
+    number(s, |w: &f64| w.is_finite(), ParseError::InvalidObjectiveWeight)
+}
+
+fn constraint(s: &str) -> Result<expr::Constraint, ParseError> {
+    expr::Constraint::parse(s).map_err(|_| ParseError::InvalidConstraint(s.to_string()))
+}
+
+fn penalty(s: &str) -> Result<f64, ParseError> {
+    number(s, |p: &f64| p.is_finite() && *p > 0.0, ParseError::InvalidPenalty)
+}
+
+fn noise(s: &str) -> Result<f64, ParseError> {
+    number(s, |sigma: &f64| sigma.is_finite() && *sigma >= 0.0, ParseError::InvalidNoise)
+}
+
+fn reeval_every(s: &str) -> Result<usize, ParseError> {
+    number(s, |k| *k > 0, ParseError::InvalidReevaluation)
+}
+
+fn polish_iterations(s: &str) -> Result<usize, ParseError> {
+    number(s, |k| *k > 0, ParseError::InvalidPolishIterations)
+}
+
+fn inertia(s: &str) -> Result<f64, ParseError> {
+    number(s, |w: &f64| w.is_finite(), ParseError::InvalidInertia)
+}
+
+fn coefficient(s: &str) -> Result<f64, ParseError> {
+    number(s, |c: &f64| c.is_finite() && *c >= 0.0, ParseError::InvalidCoefficient)
+}
+
+fn w_schedule(s: &str) -> Result<LinearSchedule, ParseError> {
+    let invalid = || ParseError::InvalidSchedule(s.to_string());
+    let weight = |w: &str| w.parse::<f64>().ok().filter(|w| w.is_finite());
+    match s.split(':').collect::<Vec<&str>>()[..] {
+        ["linear", start, end] => Ok(LinearSchedule {
+            start: weight(start).ok_or_else(invalid)?,
+            end: weight(end).ok_or_else(invalid)?,
+        }),
+        _ => Err(invalid()),
     }
 }
 
-enum OptimizationPolicy {
-    FindMinimum,
-    FindMaximum,
+fn acc_schedule(s: &str) -> Result<AccelerationSchedule, ParseError> {
+    match s {
+        "tvac" => Ok(AccelerationSchedule::tvac()),
+        _ => Err(ParseError::InvalidSchedule(s.to_string())),
+    }
 }
 
-fn update<R: rand::Rng>(
-    swarm: &mut ParticleSwarm,
-    consts: &UpdatePolicy,
-    f: fn(f64) -> f64,
-    opt: &OptimizationPolicy,
-    r: &mut R,
-) {
-    // Update the particle's position
-    for i in 0..swarm.position.len() {
-        swarm.position[i] += swarm.velocity[i];
+fn model(s: &str) -> Result<VelocityModel, ParseError> {
+    match s {
+        "inertia" => Ok(VelocityModel::Inertia),
+        "constriction" => Ok(VelocityModel::Constriction),
+        "fips" => Ok(VelocityModel::Fips),
+        "bare-bones" => Ok(VelocityModel::BareBones),
+        "charged" => Ok(VelocityModel::Charged),
+        "gcpso" => Ok(VelocityModel::Gcpso),
+        _ => Err(ParseError::InvalidModel(s.to_string())),
     }
+}
 
-    // Update the particle's best position
-    for i in 0..swarm.position.len() {
-        match opt {
-            OptimizationPolicy::FindMinimum => {
-                if f(swarm.position[i]) < f(swarm.local_optimum[i]) {
-                    swarm.local_optimum[i] = swarm.position[i];
-                }
-            }
-            OptimizationPolicy::FindMaximum => {
-                if f(swarm.position[i]) > f(swarm.local_optimum[i]) {
-                    swarm.local_optimum[i] = swarm.position[i];
-                }
+fn compared_model(s: &str) -> Result<(String, VelocityModel), ParseError> {
+    Ok((s.to_string(), model(s)?))
+}
+
+fn charge(s: &str) -> Result<f64, ParseError> {
+    number(s, |q: &f64| q.is_finite() && *q > 0.0, ParseError::InvalidCharge)
+}
+
+fn vmax(s: &str) -> Result<f64, ParseError> {
+    number(s, |v: &f64| v.is_finite() && *v > 0.0, ParseError::InvalidVelocityLimit)
+}
+
+fn mutation(s: &str) -> Result<f64, ParseError> {
+    number(s, |p| (0.0..=1.0).contains(p), ParseError::InvalidMutation)
+}
+
+fn mutation_scale(s: &str) -> Result<f64, ParseError> {
+    number(s, |s: &f64| s.is_finite() && *s >= 0.0, ParseError::InvalidMutation)
+}
+
+fn reinit_after(s: &str) -> Result<usize, ParseError> {
+    number(s, |k| *k > 0, ParseError::InvalidReinit)
+}
+
+fn weight(s: &str) -> Result<f64, ParseError> {
+    number(s, |w: &f64| w.is_finite() && *w > 0.0, ParseError::InvalidWeight)
+}
+
+fn sub_swarms(s: &str) -> Result<usize, ParseError> {
+    number(s, |s| *s > 0, ParseError::InvalidSubSwarms)
+}
+
+fn topology(s: &str) -> Result<Topology, ParseError> {
+    let invalid = || ParseError::InvalidTopology(s.to_string());
+    match s.split_once(':') {
+        None if s == "gbest" => Ok(Topology::Gbest),
+        None if s == "vonneumann" => Ok(Topology::VonNeumann),
+        Some(("ring", k)) => Ok(Topology::Ring {
+            k: k.parse::<usize>().ok().filter(|k| *k > 0).ok_or_else(invalid)?,
+        }),
+        _ => Err(invalid()),
+    }
+}
+
+fn bound(s: &str) -> Result<f64, ParseError> {
+    number(s, |b: &f64| b.is_finite(), ParseError::InvalidBounds)
+}
+
+fn interval(s: &str) -> Result<(f64, f64, Option<BoundMode>), ParseError> {
+    let value = |x: &str| x.parse::<f64>().ok().filter(|x| x.is_finite());
+    let (lo, hi, mode) = match s.split(':').collect::<Vec<&str>>()[..] {
+        [lo, hi] => (lo, hi, None),
+        [lo, hi, mode] => (lo, hi, Some(bound_mode(mode)?)),
+        _ => return Err(ParseError::InvalidBounds(s.to_string())),
+    };
+    match (value(lo), value(hi)) {
+        (Some(lo), Some(hi)) if lo < hi => Ok((lo, hi, mode)),
+        _ => Err(ParseError::InvalidBounds(s.to_string())),
+    }
+}
+
+fn bound_mode(s: &str) -> Result<BoundMode, ParseError> {
+    match s {
+        "clamp" => Ok(BoundMode::Clamp),
+        "reflect" => Ok(BoundMode::Reflect),
+        "wrap" => Ok(BoundMode::Wrap),
+        _ => Err(ParseError::InvalidBoundMode(s.to_string())),
+    }
+}
+
+fn integer_dim(s: &str) -> Result<usize, ParseError> {
+    number(s.trim(), |_| true, ParseError::InvalidIntegerDims)
+}
+
+fn fixed(s: &str) -> Result<(usize, f64), ParseError> {
+    let invalid = || ParseError::InvalidFix(s.to_string());
+    let (j, x) = s.split_once('=').ok_or_else(invalid)?;
+    let j = j.trim().parse::<usize>().map_err(|_| invalid())?;
+    let x = x.trim().parse::<f64>().ok().filter(|x| x.is_finite()).ok_or_else(invalid)?;
+    Ok((j, x))
+}
+
+fn precision(s: &str) -> Result<Precision, ParseError> {
+    match s {
+        "f64" => Ok(Precision::F64),
+        "f32" => Ok(Precision::F32),
+        _ => Err(ParseError::InvalidPrecision(s.to_string())),
+    }
+}
+
+fn format(s: &str) -> Result<OutputFormat, ParseError> {
+    match s {
+        "text" => Ok(OutputFormat::Text),
+        "json" => Ok(OutputFormat::Json),
+        "kv" => Ok(OutputFormat::Kv),
+        _ => Err(ParseError::InvalidFormat(s.to_string())),
+    }
+}
+
+fn verbose_level(s: &str) -> Result<u8, ParseError> {
+    number(s, |level| (1..=2).contains(level), ParseError::InvalidVerboseLevel)
+}
+
+fn log_level(s: &str) -> Result<LevelFilter, ParseError> {
+    s.parse::<LevelFilter>().map_err(|_| ParseError::InvalidLogLevel(s.to_string()))
+}
+
+fn seed(s: &str) -> Result<u64, ParseError> {
+    number(s.trim(), |_| true, ParseError::InvalidSeed)
+}
+
+fn restarts(s: &str) -> Result<usize, ParseError> {
+    number(s, |k| *k > 0, ParseError::InvalidRestarts)
+}
+
+fn repeats(s: &str) -> Result<usize, ParseError> {
+    number(s, |k| *k > 0, ParseError::InvalidRepeats)
+}
+
+fn value(s: &str) -> Result<f64, ParseError> {
+    number(s, |_| true, ParseError::InvalidArgument)
+}
+
+fn range(s: &str) -> Result<(f64, f64), ParseError> {
+    let invalid = || ParseError::InvalidRange(s.to_string());
+    let value = |x: &str| x.parse::<f64>().ok().filter(|x| x.is_finite());
+    match s.split(':').collect::<Vec<&str>>()[..] {
+        [lo, hi] => {
+            let range = (value(lo).ok_or_else(invalid)?, value(hi).ok_or_else(invalid)?);
+            if range.0 > range.1 {
+                return Err(invalid());
             }
+            Ok(range)
         }
+        _ => Err(invalid()),
     }
+}
 
-    // Update the swarm's global best value
-    let global_optimum = swarm
-        .local_optimum
-        .iter()
-        .max_by(|&x, &y| match opt {
-            OptimizationPolicy::FindMinimum => f(*y).partial_cmp(&f(*x)).unwrap(),
-            OptimizationPolicy::FindMaximum => f(*x).partial_cmp(&f(*y)).unwrap(),
-        })
-        .unwrap();
-    swarm.global_optimum = Some(*global_optimum);
+fn corner_count(s: &str) -> Result<usize, ParseError> {
+    number(s, |k| *k > 0, ParseError::InvalidCornerCount)
+}
 
-    // Update the particle's velocity
-    for i in 0..swarm.velocity.len() {
-        let r1 = r.gen::<f64>();
-        let r2 = r.gen::<f64>();
-        swarm.velocity[i] = swarm.velocity[i]
-            + consts.c1 * r1 * (swarm.local_optimum[i] - swarm.position[i])
-            + consts.c2 * r2 * (swarm.global_optimum.unwrap() - swarm.position[i]);
-    }
+fn grid_points(s: &str) -> Result<usize, ParseError> {
+    number(s, |k| *k > 0, ParseError::InvalidGridPoints)
 }
 
-fn usage(program: &str) {
-    println!(
-        "Usage: {} -n <n> (-e <e>|-i <i>) [-v] [--seed <seed>] [--init <x1,x2,...,xn>] [--vinit <v1,v2,...,vn>]",
-        program
-    );
-    println!("\t-n: Number of particles\t(required)");
-    println!("\t-e: Error threshold\t(default:0.0001)");
-    println!("\t-i: Number of iterations\t(uses error threshold if not provided)");
-    println!("\t-v: Verbose mode\t(default:false)");
-    println!("\t--seed: Use a fixed seed for random number generation");
-    println!("\t--init: Initial positions of particles");
-    println!("\t--vinit: Initial velocities of particles");
+fn plot_cols(s: &str) -> Result<usize, ParseError> {
+    number(s, |cols| *cols >= 2, ParseError::InvalidPlotColumns)
 }
 
-enum ParseError {
-    MissingArgument(String),
-    InvalidParticleNumber(String),
-    InvalidIterations(String),
-    InvalidThreshold(String),
-    InvalidSeed(String),
-    InvalidArgument(String),
+fn digits(s: &str) -> Result<usize, ParseError> {
+    number(s, |_| true, ParseError::InvalidDigits)
 }
 
-struct RunOptions {
-    n: usize,
-    iter: Option<usize>,
-    thresh: f64,
-    verbose: bool,
-    init: Option<Vec<f64>>,
-    vinit: Option<Vec<f64>>,
-    r: Option<rand::rngs::StdRng>,
+fn init_method(s: &str) -> Result<InitMethod, ParseError> {
+    match s {
+        "uniform" => Ok(InitMethod::Uniform),
+        "halton" => Ok(InitMethod::Halton),
+        "lhs" => Ok(InitMethod::LatinHypercube),
+        _ => Err(ParseError::InvalidInitMethod(s.to_string())),
+    }
 }
 
-fn parse(args: &Vec<String>) -> Result<RunOptions, ParseError> {
-    let mut n = None;
-    let mut iter = None;
-    let mut verbose = false;
-    let mut thresh = 0.0001;
-    let mut r = None;
+fn checkpoint_every(s: &str) -> Result<usize, ParseError> {
+    number(s, |k| *k > 0, ParseError::InvalidCheckpointInterval)
+}
 
-    let mut init = None;
-    let mut vinit = None;
+fn parse(args: Args) -> Result<RunOptions, ParseError> {
+    let file = match &args.config {
+        Some(path) => read_config(path)?,
+        None => FileConfig::default(),
+    };
 
-    let mut i = 1;
-    while i < args.len() {
-        match args[i].as_str() {
-            "-n" => {
-                if i + 1 >= args.len() {
-                    return Err(ParseError::MissingArgument("-n".to_string()));
-                }
-                n = Some(
-                    args[i + 1]
-                        .parse::<usize>()
-                        .map_err(|_| ParseError::InvalidParticleNumber(args[i + 1].clone()))?,
-                );
-                i += 2;
-            }
-            "-i" => {
-                if i + 1 >= args.len() {
-                    return Err(ParseError::MissingArgument("-i".to_string()));
-                }
-                iter = Some(
-                    args[i + 1]
-                        .parse::<usize>()
-                        .map_err(|_| ParseError::InvalidIterations(args[i + 1].clone()))?,
-                );
-                i += 2;
-            }
-            "-e" => {
-                if i + 1 >= args.len() {
-                    return Err(ParseError::MissingArgument("-e".to_string()));
-                }
-                thresh = args[i + 1]
-                    .parse::<f64>()
-                    .map_err(|_| ParseError::InvalidThreshold(args[i + 1].clone()))?;
-                i += 2;
+    // The file skips the checks the flags make as they are parsed, so its values are checked here
+    if let Some(0) = file.n {
+        return Err(ParseError::InvalidParticleNumber(0.to_string()));
+    }
+    if let Some(0) = file.dimension {
+        return Err(ParseError::InvalidDimension(0.to_string()));
+    }
+    if let Some(e) = file.threshold.filter(|e| !(e.is_finite() && *e >= 0.0)) {
+        return Err(ParseError::InvalidThreshold(e.to_string()));
+    }
+    if let Some(w) = file.w.filter(|w| !w.is_finite()) {
+        return Err(ParseError::InvalidInertia(w.to_string()));
+    }
+    if let Some(c) = [file.c1, file.c2]
+        .into_iter()
+        .flatten()
+        .find(|c| !(c.is_finite() && *c >= 0.0))
+    {
+        return Err(ParseError::InvalidCoefficient(c.to_string()));
+    }
+    if let Some(b) = [file.lower, file.upper]
+        .into_iter()
+        .flatten()
+        .find(|b| !b.is_finite())
+    {
+        return Err(ParseError::InvalidBounds(b.to_string()));
+    }
+    let objectives = match (args.f, file.objective) {
+        (Some(objectives), _) => objectives,
+        (None, Some(names)) => objective_list(&names)?,
+        (None, None) => vec![Objective::Quadratic],
+    };
+    let n = args.n.or(file.n);
+    let d = args.d.or(file.dimension).unwrap_or(1);
+    let iter = args.i.or(file.iterations);
+    let (target, target_tol) = match (args.e, file.threshold) {
+        (Some(e), _) => (Some(0.0), e),
+        (None, e) => (
+            args.target.or(e.map(|_| 0.0)),
+            args.target_tol.or(e).unwrap_or(0.0001),
+        ),
+    };
+    let w = args.w.or(file.w).unwrap_or(0.729);
+    let (c1, c2) = (args.c1.or(file.c1), args.c2.or(file.c2));
+    let (lower, upper) = (args.lower.or(file.lower), args.upper.or(file.upper));
+    let seeds = args.seed.or(file.seed.map(|seed| vec![seed]));
+    // One stream has one seed
+    if args.resume_rng && seeds.as_ref().is_some_and(|seeds| seeds.len() > 1) {
+        return Err(ParseError::ConflictingArguments(
+            "--resume-rng".to_string(),
+            "a list of seeds".to_string(),
+        ));
+    }
+    let model = match (args.random_search, args.model) {
+        (true, _) => VelocityModel::RandomSearch,
+        (false, model) => model.unwrap_or(VelocityModel::Inertia),
+    };
+
+    let bounds = match (args.bounds, lower, upper) {
+        (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
+            return Err(ParseError::ConflictingArguments(
+                "--bounds".to_string(),
+                "--lower/--upper".to_string(),
+            ))
+        }
+        (Some(intervals), None, None) => {
+            if intervals.len() != d {
+                return Err(ParseError::BoundsShapeMismatch {
+                    d,
+                    got: intervals.len(),
+                });
             }
-            "-v" => {
-                verbose = true;
-                i += 1;
+            Some(Bounds {
+                lower: intervals.iter().map(|b| b.0).collect(),
+                upper: intervals.iter().map(|b| b.1).collect(),
+                modes: intervals.iter().map(|b| b.2.unwrap_or(args.bound_mode)).collect(),
+            })
+        }
+        (None, Some(lower), Some(upper)) => {
+            if lower >= upper {
+                return Err(ParseError::InvalidBounds(format!("{}:{}", lower, upper)));
             }
-            "--seed" => {
-                if i + 1 >= args.len() {
-                    return Err(ParseError::MissingArgument("--seed".to_string()));
-                }
-                let seed = args[i + 1]
-                    .parse::<u64>()
-                    .map_err(|_| ParseError::InvalidSeed(args[i + 1].clone()))?;
-                println!("Using seed {}", seed);
-                r = Some(rand::SeedableRng::seed_from_u64(seed));
-                i += 2;
-            }
-            "--init" => {
-                if i + 1 >= args.len() {
-                    return Err(ParseError::MissingArgument("--init".to_string()));
-                }
-                init = Some(
-                    args[i + 1]
-                        .split(",")
-                        .map(|x| {
-                            x.parse::<f64>()
-                                .map_err(|_| ParseError::InvalidArgument(x.to_string()))
-                        })
-                        .collect::<Result<Vec<f64>, ParseError>>()?,
-                );
-                i += 2;
-            }
-            "--vinit" => {
-                if i + 1 >= args.len() {
-                    return Err(ParseError::MissingArgument("--vinit".to_string()));
+            Some(Bounds::uniform(lower, upper, d, args.bound_mode))
+        }
+        (None, Some(_), None) => return Err(ParseError::MissingArgument("--upper".to_string())),
+        (None, None, Some(_)) => return Err(ParseError::MissingArgument("--lower".to_string())),
+        (None, None, None) => None,
+    };
+
+    let default_c = default_coefficient(model);
+    let constricted = is_constricted(model);
+    let compares = |is: fn(VelocityModel) -> bool| {
+        args.compare_models.iter().flatten().any(|&(_, model)| is(model))
+    };
+    // Without velocities there are no terms to leave out
+    let sampled = |model| matches!(model, VelocityModel::BareBones | VelocityModel::RandomSearch);
+    if (args.no_self_best || args.no_global_best) && (sampled(model) || compares(sampled)) {
+        let flag = if args.no_self_best { "--no-self-best" } else { "--no-global-best" };
+        return Err(ParseError::ConflictingArguments(
+            flag.to_string(),
+            "--model bare-bones or --random-search".to_string(),
+        ));
+    }
+    // Bare bones and random search draw no r1 and r2, and FIPS draws one per informant
+    let undrawn = |model| {
+        matches!(
+            model,
+            VelocityModel::BareBones | VelocityModel::RandomSearch | VelocityModel::Fips
+        )
+    };
+    if args.antithetic && (undrawn(model) || compares(undrawn)) {
+        return Err(ParseError::ConflictingArguments(
+            "--antithetic".to_string(),
+            "--model bare-bones or fips or --random-search".to_string(),
+        ));
+    }
+    let charged = |model| matches!(model, VelocityModel::Charged);
+    if args.charge.is_some() && !charged(model) && !compares(charged) {
+        return Err(ParseError::MissingArgument("--model charged".to_string()));
+    }
+    if args.acc_schedule.is_some() {
+        if c1.is_some() || c2.is_some() {
+            return Err(ParseError::ConflictingArguments(
+                "--acc-schedule".to_string(),
+                "--c1/--c2".to_string(),
+            ));
+        }
+        if constricted || compares(is_constricted) {
+            return Err(ParseError::ConflictingArguments(
+                "--acc-schedule".to_string(),
+                "--model constriction or fips".to_string(),
+            ));
+        }
+        // Unlike the inertia schedule there are no constant coefficients to fall back to
+        if iter.is_none() {
+            return Err(ParseError::MissingArgument("-i".to_string()));
+        }
+    }
+    // Coefficients given apply to every compared model, the others default per model
+    let mut compared_models = Vec::new();
+    for (name, model) in args.compare_models.unwrap_or_default() {
+        let c = default_coefficient(model);
+        let (c1, c2) = (c1.unwrap_or(c), c2.unwrap_or(c));
+        if is_constricted(model) && c1 + c2 <= 4.0 {
+            return Err(ParseError::InvalidConstriction((c1 + c2).to_string()));
+        }
+        compared_models.push((name, model, c1, c2));
+    }
+    let (c1, c2) = (c1.unwrap_or(default_c), c2.unwrap_or(default_c));
+    if constricted && c1 + c2 <= 4.0 {
+        return Err(ParseError::InvalidConstriction((c1 + c2).to_string()));
+    }
+
+    let objective_parts = match args.multi_objective {
+        Some(parts) => {
+            let weights = args.objective_weights.unwrap_or(vec![1.0; parts.len()]);
+            if weights.len() != parts.len() {
+                return Err(ParseError::ObjectiveWeightCountMismatch {
+                    expected: parts.len(),
+                    got: weights.len(),
+                });
+            }
+            weights.into_iter().zip(parts).collect()
+        }
+        None => Vec::new(),
+    };
+    let expr = if objective_parts.is_empty() {
+        args.expr
+    } else {
+        Some(expr::Expression::weighted_sum(&objective_parts))
+    };
+    // Only the coordinates a particle has can be read
+    if let Some(e) = &expr {
+        if e.dimension() > d {
+            return Err(ParseError::InvalidExpression(format!("x{}", e.dimension() - 1)));
+        }
+    }
+    if let Some(c) = args.constraint.iter().find(|c| c.dimension() > d) {
+        return Err(ParseError::InvalidConstraint(c.to_string()));
+    }
+
+    let n = n.ok_or(ParseError::MissingArgument("-n".to_string()))?;
+    if let Some(j) = args.integer_dims.iter().find(|&&j| j >= d) {
+        return Err(ParseError::InvalidIntegerDims(j.to_string()));
+    }
+    for (k, &(j, x)) in args.fix.iter().enumerate() {
+        if j >= d || args.fix[..k].iter().any(|&(i, _)| i == j) {
+            return Err(ParseError::InvalidFix(format!("{}={}", j, x)));
+        }
+    }
+    if args.plot.is_some() && !cfg!(feature = "plotters") {
+        return Err(ParseError::FeatureDisabled {
+            flag: "--plot".to_string(),
+            feature: "plotters".to_string(),
+        });
+    }
+    if let Some(s) = args.cpso.filter(|&s| s > d) {
+        return Err(ParseError::TooManySubSwarms { d, s });
+    }
+    if let Some(w) = &args.weights {
+        if w.len() != n {
+            return Err(ParseError::WeightCountMismatch { n, got: w.len() });
+        }
+    }
+    // Only the final positions are known of the whole swarm
+    if args.cpso.is_some() && args.plot.is_some() && !args.plot_positions {
+        return Err(ParseError::ConflictingArguments("--cpso".to_string(), "--plot".to_string()));
+    }
+    if args.plot_positions && d != 2 {
+        return Err(ParseError::PlotDimension(d));
+    }
+    if args.plot.is_some() && objectives.len() > 1 {
+        return Err(ParseError::ConflictingArguments(
+            "--plot".to_string(),
+            "several -f functions".to_string(),
+        ));
+    }
+    // Every model is compared on the one function
+    if !compared_models.is_empty() && objectives.len() > 1 {
+        return Err(ParseError::ConflictingArguments(
+            "--compare-models".to_string(),
+            "several -f functions".to_string(),
+        ));
+    }
+    if args.ascii_plot {
+        if d > 2 {
+            return Err(ParseError::AsciiPlotDimension(d));
+        }
+        let conflict = if objectives.len() > 1 {
+            Some("several -f functions")
+        } else if args.repeats > 1 {
+            Some("--repeats")
+        } else if matches!(args.format, OutputFormat::Json) {
+            Some("--format json")
+        } else if matches!(args.format, OutputFormat::Kv) {
+            Some("--format kv")
+        } else {
+            None
+        };
+        if let Some(flag) = conflict {
+            return Err(ParseError::ConflictingArguments("--ascii-plot".to_string(), flag.to_string()));
+        }
+    }
+    // Positions of different functions are not comparable, so there is no single solution
+    if args.solution_out.is_some() && objectives.len() > 1 {
+        return Err(ParseError::ConflictingArguments(
+            "--solution-out".to_string(),
+            "several -f functions".to_string(),
+        ));
+    }
+    // Every repeat is reported on its own, which several functions would multiply
+    if args.repeats > 1 {
+        if objectives.len() > 1 {
+            return Err(ParseError::ConflictingArguments(
+                "--repeats".to_string(),
+                "several -f functions".to_string(),
+            ));
+        }
+        if args.plot.is_some() {
+            return Err(ParseError::ConflictingArguments("--repeats".to_string(), "--plot".to_string()));
+        }
+    }
+    // Listed seeds go to the repeats if there are any, and to the restarts otherwise
+    let runs = if args.repeats > 1 { args.repeats } else { args.restarts };
+    if let Some(got) = seeds.as_ref().map(Vec::len).filter(|&got| got > runs) {
+        return Err(ParseError::TooManySeeds { runs, got });
+    }
+    // A maximum has no natural value to approach, so it needs an explicit goal
+    let limited = iter.is_some() || args.max_time.is_some() || args.budget.is_some();
+    if args.maximize && !limited && target.is_none() {
+        let limits = "-i, --target, --max-time or --budget";
+        return Err(ParseError::MissingArgument(limits.to_string()));
+    }
+    // The checkpoint holds the whole swarm, and only for one run
+    if args.warm_start.is_some() {
+        let replaced = if args.particles_from.is_some() {
+            Some("--particles-from")
+        } else if args.init.is_some() {
+            Some("--init")
+        } else if args.restarts > 1 {
+            Some("--restarts")
+        } else if args.repeats > 1 {
+            Some("--repeats")
+        } else if objectives.len() > 1 {
+            Some("several -f functions")
+        } else {
+            None
+        };
+        if let Some(flag) = replaced {
+            return Err(ParseError::ConflictingArguments(
+                "--warm-start".to_string(),
+                flag.to_string(),
+            ));
+        }
+    }
+    let warm_start = match args.warm_start {
+        Some(path) => {
+            let read = Checkpoint::read(Path::new(&path));
+            Some(read.map_err(|e| ParseError::CheckpointError {
+                path,
+                message: e.to_string(),
+            })?)
+        }
+        None => None,
+    };
+    // Given positions leave nothing to draw
+    if !matches!(args.init_method, InitMethod::Uniform) {
+        let given = if args.particles_from.is_some() {
+            Some("--particles-from")
+        } else if args.init.is_some() {
+            Some("--init")
+        } else {
+            None
+        };
+        if let Some(flag) = given {
+            return Err(ParseError::ConflictingArguments("--init-method".to_string(), flag.to_string()));
+        }
+    }
+    let (init, vinit) = match &args.particles_from {
+        Some(path) => {
+            let (x, v) = read_particles(path, n, d)?;
+            (Some(x), Some(v))
+        }
+        None => {
+            if let Some(x) = &args.init {
+                if x.len() != n * d {
+                    return Err(ParseError::InitShapeMismatch { n, d, got: x.len() });
                 }
-                vinit = Some(
-                    args[i + 1]
-                        .split(",")
-                        .map(|x| {
-                            x.parse::<f64>()
-                                .map_err(|_| ParseError::InvalidArgument(x.to_string()))
-                        })
-                        .collect::<Result<Vec<f64>, ParseError>>()?,
-                );
-                i += 2;
             }
-            _ => {
-                return Err(ParseError::InvalidArgument(args[i].clone()));
+            if let Some(v) = &args.vinit {
+                if v.len() != n * d {
+                    return Err(ParseError::VInitLengthMismatch {
+                        expected: n * d,
+                        got: v.len(),
+                    });
+                }
             }
+            // Row-major: the first `d` values are the first particle, and so on
+            let reshape = |x: Vec<f64>| x.chunks(d).map(|c| c.to_vec()).collect::<Vec<Vec<f64>>>();
+            (args.init.map(reshape), args.vinit.map(reshape))
+        }
+    };
+    if let Some(x) = &args.known_optimum {
+        if x.len() != d {
+            return Err(ParseError::OptimumLengthMismatch { d, got: x.len() });
         }
     }
 
+    let verbose = args.verbose || args.verbose_level.is_some();
     Ok(RunOptions {
-        n: n.ok_or(ParseError::MissingArgument("-n".to_string()))?,
+        n,
+        d,
         iter,
-        thresh,
-        verbose,
+        max_time: args.max_time,
+        budget: args.budget,
+        maximize: args.maximize,
+        target,
+        target_tol,
+        stag_eps: args.stag_eps,
+        stag_window: args.stag_window,
+        ma_window: args.ma_window,
+        ma_stagnation: args.ma_stagnation,
+        vel_tol: args.vel_tol,
+        diameter_eps: args.diameter_eps,
+        diameter_reinit: args.diameter_reinit,
+        conv_radius: args.conv_radius,
+        objectives,
+        known_optimum: args.known_optimum,
+        expr,
+        objective_parts,
+        objective_cmd: args.objective_from_cmd,
+        constraints: args.constraint,
+        penalty: args.penalty.unwrap_or(1000.0),
+        noise: args.objective_noise,
+        reeval_best: args.reeval_best,
+        elitism: args.elitism,
+        polish: args.polish.then(|| args.polish_iters.unwrap_or(100)),
+        w,
+        w_schedule: args.w_schedule,
+        acc_schedule: args.acc_schedule,
+        c1,
+        c2,
+        model,
+        compared_models,
+        vmax: args.vmax,
+        mutation: args.mutation,
+        mutation_scale: args.mutation_scale,
+        reinit_after: args.reinit_after,
+        charge: args.charge.unwrap_or(1.0),
+        no_self_best: args.no_self_best,
+        no_global_best: args.no_global_best,
+        antithetic: args.antithetic,
+        cpso: args.cpso,
+        weights: args.weights,
+        topology: args.topology,
+        bounds,
+        integer_dims: args.integer_dims,
+        fixed: args.fix,
+        precision: args.precision,
+        quiet: args.quiet,
+        log_level: match (args.log_level, verbose, args.quiet) {
+            (Some(level), _, _) => level,
+            // The log would break up the stream of JSON lines on the same stderr
+            (None, _, _) if args.events => LevelFilter::OFF,
+            (None, true, _) => LevelFilter::TRACE,
+            (None, _, true) => LevelFilter::WARN,
+            (None, false, false) => LevelFilter::INFO,
+        },
+        dump_swarm: args.verbose_level == Some(2),
+        progress: args.progress,
+        events: args.events,
+        dry_run: args.dry_run,
+        csv: args.csv,
+        history: args.history,
+        solution_out: args.solution_out,
+        plot: args.plot,
+        plot_positions: args.plot_positions,
+        ascii_plot: args.ascii_plot,
+        plot_cols: args.plot_cols,
         init,
         vinit,
-        r,
+        init_range: args.init_range,
+        init_method: args.init_method,
+        seed_corners: args.seed_corners.unwrap_or(0),
+        obl_init: args.obl_init,
+        grid_seed: args.grid_seed,
+        vinit_range: args.vinit_range,
+        seeds,
+        restarts: args.restarts,
+        repeats: args.repeats,
+        resume_rng: args.resume_rng,
+        checkpoint: args.checkpoint,
+        checkpoint_every: args.checkpoint_every,
+        warm_start,
+        format: args.format,
+        digits: args.precision_digits,
     })
 }
 
-fn main() {
-    let args = std::env::args().collect::<Vec<String>>();
-    let run_opts = match parse(&args) {
-        Ok(opts) => opts,
-        Err(ParseError::MissingArgument(arg)) => {
-            eprintln!("Missing argument for {}", arg.red());
-            usage(&args[0]);
-            std::process::exit(1);
-        }
-        Err(ParseError::InvalidParticleNumber(arg)) => {
-            eprintln!("Invalid number of particles: {}", arg.red());
-            usage(&args[0]);
-            std::process::exit(1);
-        }
-        Err(ParseError::InvalidIterations(arg)) => {
-            eprintln!("Invalid number of iterations: {}", arg.red());
-            usage(&args[0]);
-            std::process::exit(1);
-        }
-        Err(ParseError::InvalidThreshold(arg)) => {
-            eprintln!("Invalid error threhold: {}", arg.red());
-            usage(&args[0]);
-            std::process::exit(1);
+/// The velocity update given by the flags
+fn update_policy(run_opts: &RunOptions) -> UpdatePolicy {
+    UpdatePolicy {
+        vmax: run_opts.vmax,
+        topology: run_opts.topology,
+        model: run_opts.model,
+        mutation: run_opts.mutation,
+        mutation_scale: run_opts.mutation_scale,
+        reinit_after: run_opts.reinit_after,
+        charge: run_opts.charge,
+        elitism: run_opts.elitism,
+        cognitive: !run_opts.no_self_best,
+        social: !run_opts.no_global_best,
+        antithetic: run_opts.antithetic,
+        ..UpdatePolicy::new(run_opts.w, run_opts.c1, run_opts.c2)
+    }
+}
+
+/// Makes the first Ctrl-C set the returned flag, so that the run stops with the best found so
+/// far; a second Ctrl-C ends the process at once, with the exit code of an interrupted one
+fn install_interrupt_handler() -> Option<Arc<AtomicBool>> {
+    let flag = Arc::new(AtomicBool::new(false));
+    let set = Arc::clone(&flag);
+    let mut presses = 0;
+    let handler = move || {
+        presses += 1;
+        if presses > 1 {
+            std::process::exit(130);
         }
-        Err(ParseError::InvalidSeed(arg)) => {
-            eprintln!("Invalid seed: {}", arg.red());
-            usage(&args[0]);
-            std::process::exit(1);
+        set.store(true, Ordering::Relaxed);
+    };
+    if let Err(e) = ctrlc::set_handler(handler) {
+        warn!("Could not install the Ctrl-C handler: {}", e);
+        return None;
+    }
+    Some(flag)
+}
+
+fn main() {
+    let args = match Args::try_parse() {
+        Ok(args) => args,
+        Err(e) => {
+            // Help goes to stdout and is not a failure
+            let _ = e.print();
+            std::process::exit(if e.use_stderr() { 1 } else { 0 });
         }
-        Err(ParseError::InvalidArgument(arg)) => {
-            eprintln!("Unexpected argument: {}", arg.red());
-            usage(&args[0]);
+    };
+    let run_opts = match parse(args) {
+        Ok(opts) => opts,
+        Err(e) => {
+            let _ = Args::command().error(e.kind(), e).print();
             std::process::exit(1);
         }
     };
 
+    tracing_subscriber::fmt()
+        .with_max_level(run_opts.log_level)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .with_target(false)
+        .without_time()
+        .init();
+
     let n = run_opts.n;
-    let iter = run_opts.iter;
-    let thresh = run_opts.thresh;
-    let verbose = run_opts.verbose;
-    let mut r = match run_opts.r {
-        Some(rng) => rng,
+    let (quiet, digits) = (run_opts.quiet, run_opts.digits);
+    // Always run from a known seed so that the result can be reproduced
+    let (seed, seeds) = match &run_opts.seeds {
+        Some(seeds) => {
+            match seeds[..] {
+                [seed] => info!("Using seed {}", seed),
+                _ => info!("Using seeds {:?}", seeds),
+            }
+            (seeds[0], seeds.clone())
+        }
         None => {
-            println!("Using random seed");
-            rand::SeedableRng::from_entropy()
+            let seed = rand::random::<u64>();
+            info!("Using random seed {}", seed);
+            (seed, Vec::new())
         }
     };
 
-    println!("Particle Swarm Optimization Demo");
-    println!("Function to optimize: y = (x - 1)^2");
+    info!("Particle Swarm Optimization Demo");
+    match (&run_opts.objective_cmd, &run_opts.expr) {
+        (Some(command), _) => info!("Function to optimize: y = output of {}", command),
+        (None, Some(e)) => info!("Function to optimize: y = {}", e),
+        (None, None) => {
+            for objective in &run_opts.objectives {
+                info!("Function to optimize: {}", objective.formula());
+            }
+        }
+    }
 
-    let f = |x: f64| (x - 1.0) * (x - 1.0);
-    let opt = OptimizationPolicy::FindMinimum;
-    let consts = UpdatePolicy::new(0.5, 0.5);
-    let mut swarm = match run_opts.init {
-        Some(x) => match run_opts.vinit {
-            Some(v) => ParticleSwarm::new(n, x, v, f, &opt),
-            None => ParticleSwarm::new(n, x, vec![0.0; n], f, &opt),
+    let d = run_opts.d;
+    let policy = update_policy(&run_opts);
+    // Every objective is run with the same seed, so that their results can be compared
+    let program = run_opts.objective_cmd.as_deref().map(|command| Arc::new(Program::new(command)));
+    let benchmark = program.is_none() && run_opts.expr.is_none();
+    // A second copy of the objective, drawn after the run
+    let landscape = run_opts.ascii_plot.then(|| match (&program, &run_opts.expr) {
+        (Some(p), _) => {
+            let p = Arc::clone(p);
+            Box::new(move |x: &[f64]| p.eval(x)) as ObjectiveFn
+        }
+        (None, Some(e)) => e.clone().into_fn(),
+        (None, None) => Box::new(run_opts.objectives[0].function()),
+    });
+    let (labels, mut objectives): (Vec<String>, Vec<ObjectiveFn>) = match (&program, run_opts.expr) {
+        (Some(p), _) => {
+            let p = Arc::clone(p);
+            let f = Box::new(move |x: &[f64]| p.eval(x)) as ObjectiveFn;
+            (vec![run_opts.objective_cmd.clone().unwrap()], vec![f])
+        }
+        (None, Some(e)) => (vec![e.to_string()], vec![e.into_fn()]),
+        (None, None) => run_opts
+            .objectives
+            .iter()
+            .map(|objective| {
+                (objective.name().to_string(), Box::new(objective.function()) as ObjectiveFn)
+            })
+            .unzip(),
+    };
+    // What each result is measured against, given for all functions or known for benchmarks
+    let optima = match &run_opts.known_optimum {
+        Some(x) => objectives.iter().map(|f| Some((x.clone(), f(x)))).collect(),
+        None if benchmark && !run_opts.maximize => labels
+            .iter()
+            .map(|name| benchmarks::known_optimum(name, d))
+            .collect(),
+        None => vec![None; objectives.len()],
+    };
+    for (name, optimum) in labels.iter().zip(&optima) {
+        if let Some((x, y)) = optimum {
+            debug!("Known optimum of {}: y = {} at x = {:?}", name, y, x);
+        }
+    }
+    let objective = objectives.remove(0);
+    let penalty = (!run_opts.constraints.is_empty()).then(|| Penalty {
+        constraints: run_opts.constraints.iter().cloned().map(|c| c.into_fn()).collect(),
+        coeff: run_opts.penalty,
+    });
+    if run_opts.no_self_best && run_opts.no_global_best {
+        warn!("with --no-self-best and --no-global-best the particles only drift by inertia");
+    }
+    if run_opts.w_schedule.is_some() && run_opts.iter.is_none() {
+        warn!(
+            "--w-schedule needs a fixed number of iterations, using constant inertia weight {}",
+            run_opts.w
+        );
+    }
+    // Without -i, --max-time or --budget the run goes on until it reaches the target, zero unless
+    // given
+    let limited = run_opts.max_time.is_some() || run_opts.budget.is_some();
+    let target = match (run_opts.iter, run_opts.target) {
+        (Some(_), None) => None,
+        (None, None) if limited => None,
+        (_, target) => Some(Target {
+            target: target.unwrap_or(0.0),
+            tol: run_opts.target_tol,
+            stag_eps: run_opts.stag_eps,
+            stag_window: run_opts.stag_window,
+            smoothed: run_opts.ma_stagnation,
+        }),
+    };
+    let stop = StoppingRule {
+        iterations: run_opts.iter,
+        target,
+        time: run_opts.max_time,
+        evaluations: run_opts.budget,
+    };
+    let opt = if run_opts.maximize {
+        OptimizationPolicy::FindMaximum
+    } else {
+        OptimizationPolicy::FindMinimum
+    };
+    if run_opts.dry_run {
+        println!("n: {}", n);
+        println!("d: {}", d);
+        println!("policy: {:?}", policy);
+        println!("w schedule: {:?}", run_opts.w_schedule);
+        println!("acceleration schedule: {:?}", run_opts.acc_schedule);
+        println!("bounds: {:?}", run_opts.bounds);
+        println!("integer coordinates: {:?}", run_opts.integer_dims);
+        println!("fixed coordinates: {:?}", run_opts.fixed);
+        println!("precision: {:?}", run_opts.precision);
+        println!("init method: {:?}", run_opts.init_method);
+        println!("corner particles: {}", run_opts.seed_corners.min(n));
+        println!("opposition-based init: {}", run_opts.obl_init);
+        println!("grid cells per coordinate: {:?}", run_opts.grid_seed);
+        println!("objective: {}", labels.join(", "));
+        for constraint in &run_opts.constraints {
+            println!("constraint: {} (penalty {})", constraint, run_opts.penalty);
+        }
+        println!("objective noise: {:?}", run_opts.noise);
+        println!("re-evaluation interval: {:?}", run_opts.reeval_best);
+        println!("polish rounds: {:?}", run_opts.polish);
+        println!("known optimum: {:?}", optima);
+        println!("optimization: {:?}", opt);
+        println!("stopping rule: {:?}", stop);
+        println!("velocity tolerance: {:?}", run_opts.vel_tol);
+        println!("moving average window: {:?}", run_opts.ma_window);
+        println!("diameter threshold: {:?} (reinit {})", run_opts.diameter_eps, run_opts.diameter_reinit);
+        println!("convergence radius: {}", run_opts.conv_radius);
+        if let Some(s) = run_opts.cpso {
+            println!("cooperative sub-swarms: {}", s);
+        }
+        println!("weights: {:?}", run_opts.weights);
+        println!("restarts: {}", run_opts.restarts);
+        println!("repeats: {}", run_opts.repeats);
+        for (name, _, c1, c2) in &run_opts.compared_models {
+            println!("compared model: {} (c1 {}, c2 {})", name, c1, c2);
+        }
+        println!("resume rng: {}", run_opts.resume_rng);
+        println!("seed: {}", seed);
+        if seeds.len() > 1 {
+            println!("seeds: {:?}", seeds);
+        }
+        return;
+    }
+    let csv = match &run_opts.csv {
+        Some(path) => match File::create(path) {
+            Ok(file) => Some(Box::new(BufWriter::new(file)) as Box<dyn Write>),
+            Err(e) => {
+                eprintln!("Could not create {}: {}", path.red(), e);
+                std::process::exit(1);
+            }
         },
-        None => ParticleSwarm::new_random(n, f, &opt, &mut r),
+        None => None,
+    };
+
+    info!("Initialized {} particles", n);
+    let config = PsoConfig {
+        n,
+        d,
+        policy,
+        w_schedule: run_opts.w_schedule,
+        acc_schedule: run_opts.acc_schedule,
+        opt,
+        stop,
+        vel_tol: run_opts.vel_tol,
+        ma_window: run_opts.ma_window,
+        diameter_eps: run_opts.diameter_eps,
+        diameter_reinit: run_opts.diameter_reinit,
+        conv_radius: run_opts.conv_radius,
+        cooperative: run_opts.cpso,
+        weights: run_opts.weights,
+        bounds: run_opts.bounds.clone(),
+        integer_dims: run_opts.integer_dims,
+        fixed: run_opts.fixed,
+        objective,
+        penalty,
+        noise: run_opts.noise,
+        reeval_every: run_opts.reeval_best,
+        polish: run_opts.polish,
+        program,
+        init: run_opts.init,
+        vinit: run_opts.vinit,
+        precision: run_opts.precision,
+        init_range: run_opts.init_range,
+        init_method: run_opts.init_method,
+        seed_corners: run_opts.seed_corners,
+        obl_init: run_opts.obl_init,
+        grid_seed: run_opts.grid_seed,
+        vinit_range: run_opts.vinit_range,
+        seed,
+        seeds,
+        restarts: run_opts.restarts,
+        resume_rng: run_opts.resume_rng,
+        csv,
+        record_history: run_opts.history.is_some() || (run_opts.plot.is_some() && !run_opts.plot_positions),
+        // The progress bar would be torn up by the messages of every iteration
+        progress: run_opts.progress
+            && !quiet
+            && run_opts.log_level < LevelFilter::DEBUG
+            && matches!(run_opts.format, OutputFormat::Text),
+        dump_swarm: run_opts.dump_swarm,
+        digits: run_opts.digits,
+        events: run_opts.events,
+        on_iteration: None,
+        checkpoint: run_opts.checkpoint.map(PathBuf::from),
+        checkpoint_every: run_opts.checkpoint_every,
+        warm_start: run_opts.warm_start,
+        interrupt: install_interrupt_handler(),
     };
+    let compared = &run_opts.compared_models;
+    let results = if !compared.is_empty() {
+        let policies = compared
+            .iter()
+            .map(|&(_, model, c1, c2)| UpdatePolicy { model, c1, c2, ..policy })
+            .collect::<Vec<UpdatePolicy>>();
+        run_policies(config, &policies)
+    } else if run_opts.repeats > 1 {
+        run_repeats(config, run_opts.repeats)
+    } else {
+        run_each(config, objectives)
+    };
+    let mut results = match results {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("{}", e.to_string().red());
+            std::process::exit(1);
+        }
+    };
+    // Repeats all run the one function
+    for (result, optimum) in results.iter_mut().zip(optima.iter().cycle()) {
+        if let Some((x, y)) = optimum {
+            result.compare_to_optimum(x, *y);
+        }
+    }
+    if !run_opts.objective_parts.is_empty() {
+        for result in &mut results {
+            let parts = run_opts.objective_parts.iter();
+            let values = parts.map(|(_, e)| e.eval(&result.best_position)).collect();
+            result.objective_values = Some(values);
+        }
+    }
+    // A single run keeps the output of a plain run, several give one entry per function or repeat
+    let several = results.len() > 1;
+
+    let histories = results.iter_mut().filter_map(|r| r.history.take()).collect::<Vec<History>>();
+    if let Some(path) = &run_opts.plot {
+        if let Err(e) = draw(path, run_opts.plot_positions, &results[0], histories.first()) {
+            eprintln!("Could not write {}: {}", path.red(), e);
+            std::process::exit(1);
+        }
+    }
+    if let Some(path) = &run_opts.history {
+        let written = File::create(path).and_then(|file| {
+            let mut w = BufWriter::new(file);
+            if several {
+                serde_json::to_writer(&mut w, &histories)?;
+            } else {
+                serde_json::to_writer(&mut w, &histories[0])?;
+            }
+            w.flush()
+        });
+        if let Err(e) = written {
+            eprintln!("Could not write {}: {}", path.red(), e);
+            std::process::exit(1);
+        }
+    }
+    if let Some(path) = &run_opts.solution_out {
+        let by_value = |a: &&RunResult, b: &&RunResult| a.best_value.total_cmp(&b.best_value);
+        let best = if run_opts.maximize {
+            results.iter().max_by(by_value)
+        } else {
+            results.iter().min_by(by_value)
+        };
+        if let Err(e) = write_solution(path, &best.unwrap().best_position) {
+            eprintln!("Could not write {}: {}", path.red(), e);
+            std::process::exit(1);
+        }
+    }
 
-    println!("\nInitialized {} particles:", n);
-    if verbose {
-        println!("{}\n", swarm);
-    }
-    match iter {
-        Some(i) => {
-            for _ in 1..i+1 {
-                update(&mut swarm, &consts, f, &opt, &mut r);
-                if verbose {
-                    println!("Iteration {}", i);
-                    println!("{}\n", swarm);
+    match run_opts.format {
+        OutputFormat::Text if !compared.is_empty() => {
+            let names = compared.iter().map(|(name, ..)| name.as_str()).collect::<Vec<&str>>();
+            print_comparison(&names, &results, digits);
+        }
+        OutputFormat::Text if run_opts.repeats > 1 => {
+            let summary = Summary::of(&results, run_opts.target.unwrap_or(0.0), run_opts.target_tol);
+            print_repeats(&results, &summary, digits);
+        }
+        OutputFormat::Text => {
+            for (label, result) in labels.iter().zip(&results) {
+                if several {
+                    println!("\n{}:", label);
                 }
+                print_result(result, quiet, digits);
             }
         }
-        None => {
-            let mut i = 1;
-            while f(swarm.global_optimum.unwrap()) > thresh {
-                update(&mut swarm, &consts, f, &opt, &mut r);
-                if verbose {
-                    println!("Iteration {}", i);
-                    println!("{}\n", swarm);
+        OutputFormat::Json if several => println!("{}", serde_json::to_string(&results).unwrap()),
+        OutputFormat::Json => println!("{}", serde_json::to_string(&results[0]).unwrap()),
+        OutputFormat::Kv => {
+            for (i, result) in results.iter().enumerate() {
+                if i > 0 {
+                    println!();
                 }
-                i += 1;
+                print_kv(result);
             }
-            println!("Finished in {} iterations", i);
         }
     }
+    if let Some(f) = &landscape {
+        let result = &results[0];
+        let (position, best) = (&result.final_position, &result.best_position);
+        let chart = ascii::landscape(f, run_opts.bounds.as_ref(), position, best, run_opts.plot_cols);
+        println!("\n{}", chart);
+    }
+}
+
+#[cfg(feature = "plotters")]
+fn draw(path: &str, positions: bool, result: &RunResult, history: Option<&History>) -> Result<(), Box<dyn Error>> {
+    if positions {
+        pso::plot::positions(path, &result.final_position, &result.best_position)
+    } else {
+        pso::plot::convergence(path, history.unwrap())
+    }
+}
+
+#[cfg(not(feature = "plotters"))]
+fn draw(_: &str, _: bool, _: &RunResult, _: Option<&History>) -> Result<(), Box<dyn Error>> {
+    unreachable!("--plot is rejected without the plotters feature")
+}
+
+/// Writes `x` with one coordinate per line, replacing the file
+fn write_solution(path: &str, x: &[f64]) -> std::io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    for xi in x {
+        writeln!(w, "{}", xi)?;
+    }
+    w.flush()
+}
 
-    println!("Best value of x: {}", swarm.global_optimum.unwrap());
-    println!("Best value of y: {}", f(swarm.global_optimum.unwrap()));
+/// `x` with `digits` after the decimal point, or in full without
+fn rounded(x: f64, digits: Option<usize>) -> String {
+    match digits {
+        Some(p) => format!("{:.*}", p, x),
+        None => x.to_string(),
+    }
+}
+
+/// `x` as a list like `{:?}`, with `digits` after the decimal point of every element
+fn rounded_all(x: &[f64], digits: Option<usize>) -> String {
+    match digits {
+        Some(p) => format!("{:.*?}", p, x),
+        None => format!("{:?}", x),
+    }
+}
+
+fn print_comparison(models: &[&str], results: &[RunResult], digits: Option<usize>) {
+    println!("{:>14}  {:>24}  {:>10}  stop reason", "model", "best value", "iterations");
+    for (model, result) in models.iter().zip(results) {
+        let value = rounded(result.best_value, digits);
+        println!(
+            "{:>14}  {:>24}  {:>10}  {:?}",
+            model, value, result.iterations, result.stop_reason
+        );
+    }
+}
+
+/// The result as `key=value` lines sorted by key, leaving out the timings so that runs from the
+/// same seed print the same
+fn print_kv(result: &RunResult) {
+    let position = result.best_position.iter().map(|xi| format!("{:.12e}", xi));
+    let stop_reason = serde_json::to_value(result.stop_reason).unwrap();
+    println!("best_position={}", position.collect::<Vec<String>>().join(","));
+    println!("best_value={:.12e}", result.best_value);
+    println!("evaluations={}", result.evaluations);
+    println!("iterations={}", result.iterations);
+    println!("seed={}", result.seed);
+    println!("stop_reason={}", stop_reason.as_str().unwrap());
+}
+
+fn print_repeats(results: &[RunResult], summary: &Summary, digits: Option<usize>) {
+    println!("{:>20}  {:>24}  {:>10}  stop reason", "seed", "best value", "iterations");
+    for result in results {
+        let value = rounded(result.best_value, digits);
+        println!(
+            "{:>20}  {:>24}  {:>10}  {:?}",
+            result.seed, value, result.iterations, result.stop_reason
+        );
+    }
+    println!("min: {}", rounded(summary.min, digits));
+    println!("median: {}", rounded(summary.median, digits));
+    println!("mean: {}", rounded(summary.mean, digits));
+    println!("max: {}", rounded(summary.max, digits));
+    println!("std: {}", rounded(summary.std_dev, digits));
+    println!("success rate: {:.0}%", 100.0 * summary.success_rate);
+}
+
+fn print_result(result: &RunResult, quiet: bool, digits: Option<usize>) {
+    let position = rounded_all(&result.best_position, digits);
+    let value = rounded(result.best_value, digits);
+    if quiet {
+        println!("{}", position);
+        println!("{}", value);
+        return;
+    }
+    match result.stop_reason {
+        StopReason::Iterations => {
+            println!("Ran all {} iterations", result.iterations)
+        }
+        StopReason::Target => {
+            println!("Reached the target value after {} iterations", result.iterations)
+        }
+        StopReason::Stagnation => {
+            println!("Converged (stagnation) after {} iterations", result.iterations)
+        }
+        StopReason::VelocityTolerance => {
+            println!("Converged (swarm stopped moving) after {} iterations", result.iterations)
+        }
+        StopReason::Callback => {
+            println!("Stopped by the iteration callback after {} iterations", result.iterations)
+        }
+        StopReason::TimeLimit => {
+            println!("Stopped (time limit) after {} iterations", result.iterations)
+        }
+        StopReason::Interrupted => {
+            println!("Interrupted after {} iterations", result.iterations)
+        }
+        StopReason::EvaluationBudget => {
+            println!("Stopped (evaluation budget) after {} iterations", result.iterations)
+        }
+    }
+    println!(
+        "Took {:.3}s ({:.0} iterations/s)",
+        result.elapsed_secs, result.iterations_per_sec
+    );
+    println!("Evaluated the objective {} times", result.evaluations);
+    if result.nan_evaluations > 0 {
+        warn!("the objective was NaN at {} positions", result.nan_evaluations);
+    }
+    if let Some(k) = result.collapsed_at {
+        warn!(
+            "the swarm collapsed short of the target after {} iterations, it may have converged prematurely",
+            k
+        );
+    }
+    println!("Spread per dimension: {}", rounded_all(&result.spread.std_dev, digits));
+    print!(
+        "{:.0}% of the particles within {} of the best",
+        100.0 * result.spread.within_radius,
+        result.spread.radius
+    );
+    if result.spread.diverse() {
+        print!(", {}", "swarm did not converge (diverse)".yellow());
+    }
+    println!();
+    println!("Best value of x: {}", position);
+    println!("Best value of y: {}", value);
+    if let Some(values) = &result.objective_values {
+        println!("Value of every objective: {}", rounded_all(values, digits));
+    }
+    if let Some(before) = result.pre_polish_value {
+        println!("Polish: best value {} before, {} after", rounded(before, digits), value);
+    }
+    if let (Some(error), Some(gap)) = (result.position_error, result.value_gap) {
+        let (error, gap) = (rounded(error, digits), rounded(gap, digits));
+        println!("Distance from the known optimum: {} in x, {} in y", error, gap);
+    }
+    match result.feasible {
+        Some(true) => println!("The best position satisfies every constraint"),
+        Some(false) => println!("{}", "The best position violates a constraint".yellow()),
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(args: &[&str]) -> Result<RunOptions, ParseError> {
+        parse(Args::try_parse_from(std::iter::once("pso").chain(args.iter().copied())).unwrap())
+    }
+
+    fn options(args: &[&str]) -> RunOptions {
+        parsed(args).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    const EXAMPLE_CONFIG: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/config.toml");
+
+    #[test]
+    fn example_config_is_read() {
+        let file = read_config(EXAMPLE_CONFIG).unwrap_or_else(|e| panic!("{}", e));
+        assert_eq!((file.n, file.dimension, file.iterations), (Some(40), Some(2), Some(200)));
+        assert_eq!((file.w, file.c1, file.c2), (Some(0.729), Some(1.49445), Some(1.49445)));
+        assert_eq!((file.lower, file.upper), (Some(-5.12), Some(5.12)));
+        assert_eq!((file.threshold, file.seed), (None, Some(42)));
+        assert_eq!(file.objective.as_deref(), Some("rastrigin"));
+    }
+
+    #[test]
+    fn flags_take_the_place_of_the_config() {
+        let run_opts = options(&["--config", EXAMPLE_CONFIG, "-n", "10"]);
+        assert_eq!((run_opts.n, run_opts.d, run_opts.iter), (10, 2, Some(200)));
+        assert_eq!(run_opts.seeds, Some(vec![42]));
+        assert!(matches!(run_opts.objectives[..], [Objective::Rastrigin]));
+    }
+
+    #[test]
+    fn unknown_config_keys_are_rejected() {
+        let path = std::env::temp_dir().join(format!("pso-test-{}.toml", std::process::id()));
+        std::fs::write(&path, "particles = 40\n").unwrap();
+        let error = read_config(path.to_str().unwrap()).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(error, ParseError::ConfigError { .. }));
+    }
+
+    #[test]
+    fn value_parsers_reject_what_they_cannot_use() {
+        assert!(matches!(particles("0"), Err(ParseError::InvalidParticleNumber(_))));
+        assert!(matches!(dimension("two"), Err(ParseError::InvalidDimension(_))));
+        assert!(matches!(threshold("-1e-3"), Err(ParseError::InvalidThreshold(_))));
+        assert!(matches!(threshold("inf"), Err(ParseError::InvalidThreshold(_))));
+        assert!(matches!(target("NaN"), Err(ParseError::InvalidTarget(_))));
+        assert!(matches!(stag_window("0"), Err(ParseError::InvalidStagnation(_))));
+        assert!(matches!(ma_window("0"), Err(ParseError::InvalidWindow(_))));
+        assert!(matches!(max_time("0"), Err(ParseError::InvalidMaxTime(_))));
+        assert!(matches!(budget("0"), Err(ParseError::InvalidBudget(_))));
+        assert!(matches!(inertia("inf"), Err(ParseError::InvalidInertia(_))));
+        assert!(matches!(coefficient("-0.5"), Err(ParseError::InvalidCoefficient(_))));
+        assert!(matches!(w_schedule("linear:0.9"), Err(ParseError::InvalidSchedule(_))));
+        assert!(matches!(model("hybrid"), Err(ParseError::InvalidModel(_))));
+        assert!(matches!(vmax("0"), Err(ParseError::InvalidVelocityLimit(_))));
+        assert!(matches!(mutation("1.5"), Err(ParseError::InvalidMutation(_))));
+        assert!(matches!(weight("0"), Err(ParseError::InvalidWeight(_))));
+        assert!(matches!(topology("ring:0"), Err(ParseError::InvalidTopology(_))));
+        assert!(matches!(interval("5:-5"), Err(ParseError::InvalidBounds(_))));
+        assert!(matches!(interval("-5:5:bounce"), Err(ParseError::InvalidBoundMode(_))));
+        assert!(matches!(fixed("1"), Err(ParseError::InvalidFix(_))));
+        assert!(matches!(precision("f16"), Err(ParseError::InvalidPrecision(_))));
+        assert!(matches!(range("1:0"), Err(ParseError::InvalidRange(_))));
+        assert!(matches!(format("xml"), Err(ParseError::InvalidFormat(_))));
+        assert!(matches!(verbose_level("3"), Err(ParseError::InvalidVerboseLevel(_))));
+        assert!(matches!(seed("-1"), Err(ParseError::InvalidSeed(_))));
+        assert!(matches!(restarts("0"), Err(ParseError::InvalidRestarts(_))));
+        assert!(matches!(plot_cols("1"), Err(ParseError::InvalidPlotColumns(_))));
+        assert!(matches!(checkpoint_every("0"), Err(ParseError::InvalidCheckpointInterval(_))));
+    }
+
+    #[test]
+    fn init_needs_n_times_d_values() {
+        let error = parsed(&["-n", "2", "-d", "2", "-i", "1", "--init", "1,2,3"]).err().unwrap();
+        assert!(matches!(error, ParseError::InitShapeMismatch { n: 2, d: 2, got: 3 }));
+        let args = ["-n", "2", "-d", "2", "-i", "1", "--init", "1,2,3,4", "--vinit", "0,0"];
+        let error = parsed(&args).err().unwrap();
+        assert!(matches!(error, ParseError::VInitLengthMismatch { expected: 4, got: 2 }));
+    }
+
+    #[test]
+    fn acceleration_coefficients_reach_the_policy() {
+        let policy = update_policy(&options(&["-n", "4", "-i", "1", "--c1", "2.0", "--c2", "1.5"]));
+        assert_eq!((policy.c1, policy.c2), (2.0, 1.5));
+        assert!(matches!(policy.model, VelocityModel::Inertia));
+    }
 }
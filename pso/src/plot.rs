@@ -0,0 +1,61 @@
+//! SVG charts of a finished run, available with the `plotters` feature.
+
+use crate::History;
+use plotters::prelude::*;
+use std::error::Error;
+
+const SIZE: (u32, u32) = (800, 600);
+
+/// Draws the global best value against the iteration
+pub fn convergence(path: &str, history: &History) -> Result<(), Box<dyn Error>> {
+    let values = history.iterations.iter().map(|s| s.global_best).collect::<Vec<f64>>();
+    let (lo, hi) = range(values.iter().copied());
+
+    let root = SVGBackend::new(path, SIZE).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Convergence", ("sans-serif", 24))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(80)
+        .build_cartesian_2d(1..values.len().max(2), lo..hi)?;
+    chart.configure_mesh().x_desc("iteration").y_desc("global best").draw()?;
+    chart.draw_series(LineSeries::new(
+        values.iter().enumerate().map(|(k, y)| (k + 1, *y)),
+        &BLUE,
+    ))?;
+    root.present()?;
+    Ok(())
+}
+
+/// Draws the final positions of a two-dimensional swarm, with the best position highlighted
+pub fn positions(path: &str, position: &[Vec<f64>], best: &[f64]) -> Result<(), Box<dyn Error>> {
+    let (x_lo, x_hi) = range(position.iter().map(|x| x[0]).chain([best[0]]));
+    let (y_lo, y_hi) = range(position.iter().map(|x| x[1]).chain([best[1]]));
+
+    let root = SVGBackend::new(path, SIZE).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Final positions", ("sans-serif", 24))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(80)
+        .build_cartesian_2d(x_lo..x_hi, y_lo..y_hi)?;
+    chart.configure_mesh().x_desc("x").y_desc("y").draw()?;
+    chart.draw_series(position.iter().map(|x| Circle::new((x[0], x[1]), 3, BLUE.filled())))?;
+    chart.draw_series([Cross::new((best[0], best[1]), 6, RED.stroke_width(2))])?;
+    root.present()?;
+    Ok(())
+}
+
+/// The range of finite `values`, widened so that a constant series still gets an axis
+fn range(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (lo, hi) = values
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    if lo > hi {
+        return (0.0, 1.0);
+    }
+    let margin = if hi > lo { 0.05 * (hi - lo) } else { 0.5 };
+    (lo - margin, hi + margin)
+}
@@ -0,0 +1,110 @@
+//! Local refinement of the best position once the swarm has stopped, which pins down in a few
+//! evaluations the digits that the swarm only approaches slowly.
+
+use crate::{BoundMode, PsoConfig, RunResult};
+use tracing::debug;
+
+/// Steps below this distance are not worth an evaluation, relative to the magnitude of the
+/// coordinate
+const RELATIVE_STEP: f64 = f64::EPSILON;
+
+/// Improves the best position of `result` with [`compass_search`] for up to `iterations` rounds
+/// and `budget` evaluations, recording the value it had in [`RunResult::pre_polish_value`]
+pub(crate) fn apply(
+    config: &PsoConfig,
+    iterations: usize,
+    budget: Option<usize>,
+    f: &(dyn Fn(&[f64]) -> f64 + Sync),
+    result: &mut RunResult,
+) {
+    let before = result.best_value;
+    let (x, spread) = (&result.best_position, &result.spread.std_dev);
+    let (x, y) = compass_search(config, iterations, budget, f, x, before, spread);
+    debug!(before, after = y, "polished the best position");
+    result.pre_polish_value = Some(before);
+    (result.best_position, result.best_value) = (x, y);
+}
+
+/// Compass search from `x` with value `y`: every round tries a step forth and back along each
+/// coordinate in turn, moving as soon as one improves, and halves the steps after a round without
+/// any; the first steps are the spread of the swarm along each coordinate. It stops early once
+/// the steps are too small to matter or after `budget` evaluations.
+///
+/// Steps are clamped to the bounds of `config`, or wrapped around periodic ones, and its integer
+/// and fixed coordinates are left as they are.
+pub fn compass_search(
+    config: &PsoConfig,
+    iterations: usize,
+    budget: Option<usize>,
+    f: &(dyn Fn(&[f64]) -> f64 + Sync),
+    x: &[f64],
+    y: f64,
+    spread: &[f64],
+) -> (Vec<f64>, f64) {
+    let fixed = |j: &usize| config.fixed.iter().any(|(i, _)| i == j);
+    let movable = (0..x.len())
+        .filter(|j| !config.integer_dims.contains(j) && !fixed(j))
+        .collect::<Vec<usize>>();
+    let (mut x, mut y) = (x.to_vec(), y);
+    let mut step = spread.to_vec();
+    let mut evaluations = 0;
+    for _ in 0..iterations {
+        let mut improved = false;
+        for &j in &movable {
+            for sign in [1.0, -1.0] {
+                let mut candidate = x.clone();
+                candidate[j] += sign * step[j];
+                if let Some(b) = &config.bounds {
+                    let (lower, upper) = b.range(j);
+                    match b.modes[j] {
+                        BoundMode::Wrap => b.apply(j, &mut candidate[j], &mut 0.0),
+                        BoundMode::Clamp | BoundMode::Reflect => {
+                            candidate[j] = candidate[j].clamp(lower, upper)
+                        }
+                    }
+                }
+                if budget.is_some_and(|budget| evaluations >= budget) {
+                    return (x, y);
+                }
+                evaluations += 1;
+                let value = f(&candidate);
+                if config.opt.better(value, y) {
+                    (x, y, improved) = (candidate, value, true);
+                    break;
+                }
+            }
+        }
+        if !improved {
+            for &j in &movable {
+                // A collapsed swarm has no spread to start from
+                step[j] = if step[j] > 0.0 { step[j] / 2.0 } else { RELATIVE_STEP.sqrt() };
+            }
+            if movable.iter().all(|&j| step[j] <= RELATIVE_STEP * x[j].abs().max(1.0)) {
+                break;
+            }
+        }
+    }
+    (x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::config;
+
+    #[test]
+    fn compass_search_never_gets_worse() {
+        let f = |x: &[f64]| (x[0] - 1.0).powi(2) + 10.0 * (x[1] + 2.0).powi(2);
+        let config = config(1, 2, f);
+        let (x, y) = ([3.0, 3.0], f(&[3.0, 3.0]));
+        let mut last = y;
+        for rounds in 1..=60 {
+            let (_, value) = compass_search(&config, rounds, None, &f, &x, y, &[1.0, 1.0]);
+            assert!(value <= last);
+            last = value;
+        }
+        let (x, y) = compass_search(&config, 200, None, &f, &x, y, &[1.0, 1.0]);
+        assert!((x[0] - 1.0).abs() < 1e-6 && (x[1] + 2.0).abs() < 1e-6);
+        assert!(y < 1e-10);
+    }
+}
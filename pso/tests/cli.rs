@@ -0,0 +1,103 @@
+//! Runs of the `pso` binary, checked by what it prints.
+
+use std::process::{Command, Output};
+
+fn pso(args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_pso")).args(args).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+fn stdout(args: &[&str]) -> String {
+    String::from_utf8(pso(args).stdout).unwrap()
+}
+
+fn stderr(args: &[&str]) -> String {
+    String::from_utf8(pso(args).stderr).unwrap()
+}
+
+#[test]
+fn verbose_logs_every_iteration() {
+    let log = stderr(&["-n", "4", "-i", "3", "-v", "--seed", "1"]);
+    for i in 1..=3 {
+        assert!(log.contains(&format!("iteration={} ", i)), "{}", log);
+    }
+    assert!(!log.contains("iteration=4 "));
+}
+
+#[test]
+fn dry_run_prints_no_result() {
+    let out = stdout(&["-n", "4", "-i", "3", "--seed", "1", "--dry-run"]);
+    assert!(out.contains("n: 4"), "{}", out);
+    assert!(!out.contains("Best value"), "{}", out);
+}
+
+#[test]
+fn solution_file_is_overwritten() {
+    let path = std::env::temp_dir().join(format!("pso-solution-{}.txt", std::process::id()));
+    std::fs::write(&path, "1\n2\n3\n4\n5\n").unwrap();
+    let args = ["-n", "4", "-d", "2", "-i", "5", "--seed", "1", "--quiet", "--solution-out"];
+    pso(&[&args[..], &[path.to_str().unwrap()]].concat());
+    let solution = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let lines = solution.lines().collect::<Vec<&str>>();
+    assert_eq!(lines.len(), 2, "{}", solution);
+    assert!(lines.iter().all(|line| line.parse::<f64>().is_ok()));
+}
+
+#[test]
+fn model_comparison_has_a_row_per_model() {
+    let models = ["inertia", "constriction", "bare-bones"];
+    let args = ["-n", "4", "-i", "5", "--seed", "1", "--compare-models"];
+    let out = stdout(&[&args[..], &[models.join(",").as_str()]].concat());
+    let lines = out.lines().collect::<Vec<&str>>();
+    assert_eq!(lines.len(), 1 + models.len(), "{}", out);
+    assert!(lines[0].trim_start().starts_with("model"), "{}", out);
+    for (line, model) in lines[1..].iter().zip(models) {
+        assert_eq!(line.split_whitespace().next(), Some(model), "{}", out);
+        assert!(line.ends_with("Iterations"), "{}", out);
+    }
+}
+
+#[test]
+fn seeded_runs_print_the_same() {
+    let args = ["-n", "6", "-d", "3", "-i", "20", "--seed", "3", "--format", "kv"];
+    let (first, second) = (stdout(&args), stdout(&args));
+    assert_eq!(first, second);
+    assert!(first.contains("seed=3\n"), "{}", first);
+    let other = stdout(&["-n", "6", "-d", "3", "-i", "20", "--seed", "4", "--format", "kv"]);
+    assert_ne!(first, other);
+}
+
+#[test]
+fn precision_digits_sets_the_decimals_printed() {
+    let out = stdout(&["-n", "4", "-d", "2", "-i", "5", "--seed", "1", "--precision-digits", "3"]);
+    let line = |prefix: &str| {
+        let line = out.lines().find(|line| line.starts_with(prefix));
+        line.unwrap_or_else(|| panic!("{}", out)).trim_start_matches(prefix).to_string()
+    };
+    let decimals = |number: &str| number.split_once('.').map(|(_, fraction)| fraction.len());
+    let x = line("Best value of x: ");
+    let x = x.trim_matches(|c| c == '[' || c == ']').split(", ").collect::<Vec<&str>>();
+    assert_eq!(x.len(), 2, "{}", out);
+    assert!(x.iter().all(|xj| decimals(xj) == Some(3)), "{}", out);
+    assert_eq!(decimals(&line("Best value of y: ")), Some(3), "{}", out);
+}
+
+#[test]
+fn events_are_the_only_lines_on_stderr() {
+    let log = stderr(&["-n", "4", "-i", "3", "--seed", "1", "--events"]);
+    let events = log.lines().map(serde_json::from_str::<serde_json::Value>);
+    let events = events.collect::<Result<Vec<_>, _>>();
+    let events = events.unwrap_or_else(|e| panic!("{}: {}", e, log));
+    assert_eq!(events.len(), 1 + 3 + 1, "{}", log);
+    assert_eq!(events[0]["event"], "init");
+    assert_eq!(events[4]["event"], "done");
+}
+
+#[test]
+fn grid_seed_takes_a_range_of_a_single_value() {
+    let args = ["-n", "5", "-d", "2", "-i", "3", "--seed", "1", "--format", "kv"];
+    let out = stdout(&[&args[..], &["--grid-seed", "3", "--init-range", "2:2"]].concat());
+    assert!(out.contains("iterations=3\n"), "{}", out);
+}